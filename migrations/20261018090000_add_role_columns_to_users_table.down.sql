@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE users
+    DROP COLUMN is_admin,
+    DROP COLUMN is_disabled;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN is_admin     boolean NOT NULL DEFAULT false,
+    ADD COLUMN is_disabled  boolean NOT NULL DEFAULT false;
+
+-- Promote the owner of an existing instance (the first registered user).
+UPDATE users SET is_admin = true WHERE id = (SELECT min(id) FROM users);
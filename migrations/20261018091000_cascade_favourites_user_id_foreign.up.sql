@@ -0,0 +1,6 @@
+-- Add up migration script here
+ALTER TABLE favourites
+    DROP CONSTRAINT favourites_user_id_foreign,
+    ADD CONSTRAINT favourites_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE CASCADE;
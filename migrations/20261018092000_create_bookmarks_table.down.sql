@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE bookmarks;
+
+ALTER TABLE users
+    DROP COLUMN bookmarks_sync_timestamp;
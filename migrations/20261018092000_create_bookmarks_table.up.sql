@@ -0,0 +1,28 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN bookmarks_sync_timestamp bigint NULL;
+
+CREATE TABLE bookmarks (
+    manga_id    bigint      NOT NULL,
+    page_id     bigint      NOT NULL,
+    chapter_id  bigint      NOT NULL,
+    page        int         NOT NULL,
+    scroll      int         NOT NULL,
+    image_url   text        NOT NULL,
+    created_at  bigint      NOT NULL,
+    percent     real        NOT NULL,
+    deleted_at  bigint      NOT NULL,
+    user_id     bigint      NOT NULL,
+
+    PRIMARY KEY (user_id, manga_id, page_id),
+
+    CONSTRAINT bookmarks_manga_id_foreign
+        FOREIGN KEY (manga_id) REFERENCES mangas (id),
+
+    CONSTRAINT bookmarks_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE CASCADE
+);
+
+CREATE INDEX bookmarks_manga_id_index
+    ON bookmarks (manga_id);
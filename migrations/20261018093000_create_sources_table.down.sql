@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE sources;
+
+ALTER TABLE users
+    DROP COLUMN sources_sync_timestamp;
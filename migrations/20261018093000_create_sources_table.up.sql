@@ -0,0 +1,20 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN sources_sync_timestamp bigint NULL;
+
+CREATE TABLE sources (
+    source      varchar(120)    NOT NULL,
+    enabled     boolean         NOT NULL,
+    pinned      boolean         NOT NULL,
+    sort_key    int             NOT NULL,
+    added_in    int             NOT NULL,
+    used_at     bigint          NOT NULL,
+    deleted_at  bigint          NOT NULL,
+    user_id     bigint          NOT NULL,
+
+    PRIMARY KEY (user_id, source),
+
+    CONSTRAINT sources_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE settings;
+
+ALTER TABLE users
+    DROP COLUMN settings_sync_timestamp;
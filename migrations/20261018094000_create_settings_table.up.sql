@@ -0,0 +1,18 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN settings_sync_timestamp bigint NULL;
+
+CREATE TABLE settings (
+    namespace   varchar(120)    NOT NULL,
+    "key"       varchar(255)    NOT NULL,
+    value       jsonb           NOT NULL,
+    updated_at  bigint          NOT NULL,
+    deleted_at  bigint          NOT NULL,
+    user_id     bigint          NOT NULL,
+
+    PRIMARY KEY (user_id, namespace, "key"),
+
+    CONSTRAINT settings_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE tracks;
+
+ALTER TABLE users
+    DROP COLUMN tracks_sync_timestamp;
@@ -0,0 +1,27 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN tracks_sync_timestamp bigint NULL;
+
+CREATE TABLE tracks (
+    manga_id            bigint  NOT NULL,
+    last_chapter_id     bigint  NOT NULL,
+    chapters_total      int     NOT NULL,
+    chapters_new        int     NOT NULL,
+    last_check_at       bigint  NOT NULL,
+    last_chapter_date   bigint  NOT NULL,
+    updated_at          bigint  NOT NULL,
+    deleted_at          bigint  NOT NULL,
+    user_id             bigint  NOT NULL,
+
+    PRIMARY KEY (user_id, manga_id),
+
+    CONSTRAINT tracks_manga_id_foreign
+        FOREIGN KEY (manga_id) REFERENCES mangas (id),
+
+    CONSTRAINT tracks_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE CASCADE
+);
+
+CREATE INDEX tracks_manga_id_index
+    ON tracks (manga_id);
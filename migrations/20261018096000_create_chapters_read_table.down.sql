@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE chapters_read;
+
+ALTER TABLE users
+    DROP COLUMN chapters_sync_timestamp;
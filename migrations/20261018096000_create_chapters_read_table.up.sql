@@ -0,0 +1,21 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN chapters_sync_timestamp bigint NULL;
+
+CREATE TABLE chapters_read (
+    manga_id    bigint  NOT NULL,
+    chapter_id  bigint  NOT NULL,
+    read_at     bigint  NOT NULL,
+    percent     real    NOT NULL,
+    deleted_at  bigint  NOT NULL,
+    user_id     bigint  NOT NULL,
+
+    PRIMARY KEY (user_id, manga_id, chapter_id),
+
+    CONSTRAINT chapters_read_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE CASCADE
+);
+
+CREATE INDEX chapters_read_user_id_read_at_index
+    ON chapters_read (user_id, read_at);
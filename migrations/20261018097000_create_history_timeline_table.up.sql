@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE history_timeline (
+    id          bigserial   PRIMARY KEY,
+    manga_id    bigint      NOT NULL,
+    chapter_id  bigint      NOT NULL,
+    page        smallint    NOT NULL,
+    percent     real        NOT NULL,
+    recorded_at bigint      NOT NULL,
+    user_id     bigint      NOT NULL,
+
+    CONSTRAINT history_timeline_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE CASCADE
+);
+
+CREATE INDEX history_timeline_user_id_recorded_at_index
+    ON history_timeline (user_id, recorded_at DESC);
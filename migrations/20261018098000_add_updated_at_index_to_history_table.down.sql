@@ -0,0 +1,2 @@
+-- Add down migration script here
+DROP INDEX history_user_id_updated_at_index;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+CREATE INDEX history_user_id_updated_at_index
+    ON history (user_id, updated_at DESC);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP INDEX categories_public_slug_unique;
+
+ALTER TABLE categories
+    DROP COLUMN public_slug,
+    DROP COLUMN public_hide_nsfw;
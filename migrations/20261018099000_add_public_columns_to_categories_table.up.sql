@@ -0,0 +1,7 @@
+-- Add up migration script here
+ALTER TABLE categories
+    ADD COLUMN public_slug      varchar(32)     NULL,
+    ADD COLUMN public_hide_nsfw boolean         NOT NULL DEFAULT false;
+
+CREATE UNIQUE INDEX categories_public_slug_unique
+    ON categories (public_slug);
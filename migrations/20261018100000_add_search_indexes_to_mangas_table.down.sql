@@ -0,0 +1,9 @@
+-- Add down migration script here
+DROP INDEX mangas_source_index;
+DROP INDEX mangas_author_trgm_index;
+DROP INDEX mangas_alt_title_trgm_index;
+DROP INDEX mangas_title_trgm_index;
+DROP INDEX mangas_search_vector_index;
+
+ALTER TABLE mangas
+    DROP COLUMN search_vector;
@@ -0,0 +1,24 @@
+-- Add up migration script here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE mangas
+    ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(title, '')), 'A')
+        || setweight(to_tsvector('simple', coalesce(alt_title, '')), 'B')
+        || setweight(to_tsvector('simple', coalesce(author, '')), 'C')
+    ) STORED;
+
+CREATE INDEX mangas_search_vector_index
+    ON mangas USING GIN (search_vector);
+
+CREATE INDEX mangas_title_trgm_index
+    ON mangas USING GIN (title gin_trgm_ops);
+
+CREATE INDEX mangas_alt_title_trgm_index
+    ON mangas USING GIN (alt_title gin_trgm_ops);
+
+CREATE INDEX mangas_author_trgm_index
+    ON mangas USING GIN (author gin_trgm_ops);
+
+CREATE INDEX mangas_source_index
+    ON mangas (source);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE manga_revisions;
+
+DROP TABLE manga_reports;
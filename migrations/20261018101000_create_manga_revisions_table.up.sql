@@ -0,0 +1,51 @@
+-- Add up migration script here
+CREATE TABLE manga_reports (
+    manga_id        bigint          NOT NULL,
+    user_id         bigint          NOT NULL,
+    title           varchar(255)    NOT NULL,
+    alt_title       varchar(255)    NULL,
+    cover_url       varchar(255)    NOT NULL,
+    large_cover_url varchar(255)    NULL,
+    state           varchar(24)     NULL,
+    author          varchar(120)    NULL,
+    is_nsfw         boolean         NOT NULL,
+    changed_at      bigint          NOT NULL,
+    reported_at     bigint          NOT NULL,
+
+    PRIMARY KEY (manga_id, user_id),
+
+    -- Reports are written before the upsert that creates new manga.
+    CONSTRAINT manga_reports_manga_id_foreign
+        FOREIGN KEY (manga_id) REFERENCES mangas (id)
+            ON DELETE CASCADE
+            DEFERRABLE INITIALLY DEFERRED,
+
+    CONSTRAINT manga_reports_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE CASCADE
+);
+
+CREATE INDEX manga_reports_user_id_index
+    ON manga_reports (user_id);
+
+CREATE TABLE manga_revisions (
+    id          bigserial   PRIMARY KEY,
+    manga_id    bigint      NOT NULL,
+    user_id     bigint      NULL,
+    changes     jsonb       NOT NULL,
+    created_at  bigint      NOT NULL,
+
+    CONSTRAINT manga_revisions_manga_id_foreign
+        FOREIGN KEY (manga_id) REFERENCES mangas (id)
+            ON DELETE CASCADE,
+
+    CONSTRAINT manga_revisions_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE SET NULL
+);
+
+CREATE INDEX manga_revisions_manga_id_index
+    ON manga_revisions (manga_id, id DESC);
+
+CREATE INDEX manga_revisions_user_id_index
+    ON manga_revisions (user_id);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE users
+    DROP COLUMN links_updated_at;
+
+DROP TABLE IF EXISTS manga_links;
+DROP TABLE IF EXISTS manga_link_groups;
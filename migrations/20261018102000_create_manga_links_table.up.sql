@@ -0,0 +1,40 @@
+-- Add up migration script here
+CREATE TABLE manga_link_groups (
+    id          bigserial   PRIMARY KEY,
+    user_id     bigint      NOT NULL,
+    created_at  bigint      NOT NULL,
+
+    CONSTRAINT manga_link_groups_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE CASCADE
+);
+
+CREATE INDEX manga_link_groups_user_id_index
+    ON manga_link_groups (user_id);
+
+CREATE TABLE manga_links (
+    group_id    bigint  NOT NULL,
+    user_id     bigint  NOT NULL,
+    manga_id    bigint  NOT NULL,
+
+    PRIMARY KEY (user_id, manga_id),
+
+    CONSTRAINT manga_links_group_id_foreign
+        FOREIGN KEY (group_id) REFERENCES manga_link_groups (id)
+            ON DELETE CASCADE,
+
+    CONSTRAINT manga_links_user_id_foreign
+        FOREIGN KEY (user_id) REFERENCES users (id)
+            ON DELETE CASCADE,
+
+    -- Links never keep a manga in the catalog.
+    CONSTRAINT manga_links_manga_id_foreign
+        FOREIGN KEY (manga_id) REFERENCES mangas (id)
+            ON DELETE CASCADE
+);
+
+CREATE INDEX manga_links_group_id_index
+    ON manga_links (group_id);
+
+ALTER TABLE users
+    ADD COLUMN links_updated_at bigint NULL;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN share_reading_signals boolean NOT NULL DEFAULT true;
@@ -0,0 +1,31 @@
+-- Add down migration script here
+ALTER TABLE users
+    ADD COLUMN show_nsfw boolean NULL;
+
+UPDATE users
+SET
+    show_nsfw = max_content_rating = 2
+WHERE
+    max_content_rating IS NOT NULL;
+
+ALTER TABLE users
+    DROP COLUMN max_content_rating;
+
+ALTER TABLE manga_reports
+    ADD COLUMN is_nsfw boolean NOT NULL DEFAULT false;
+
+UPDATE manga_reports SET is_nsfw = COALESCE(UPPER(content_rating) = 'ADULT', false);
+
+ALTER TABLE manga_reports
+    ALTER COLUMN is_nsfw DROP DEFAULT,
+    DROP COLUMN content_rating;
+
+ALTER TABLE mangas
+    ADD COLUMN is_nsfw boolean NOT NULL DEFAULT false;
+
+UPDATE mangas SET is_nsfw = content_level = 2;
+
+ALTER TABLE mangas
+    ALTER COLUMN is_nsfw DROP DEFAULT,
+    DROP COLUMN content_level,
+    DROP COLUMN content_rating;
@@ -0,0 +1,37 @@
+-- Add up migration script here
+-- The rating is kept as synced so clients get back exactly what they sent, the level
+-- (see `ContentRating`, unknown ratings count as safe) is what filters compare against.
+ALTER TABLE mangas
+    ADD COLUMN content_rating varchar(24) NULL;
+
+UPDATE mangas SET content_rating = 'ADULT' WHERE is_nsfw;
+
+ALTER TABLE mangas
+    DROP COLUMN is_nsfw,
+    ADD COLUMN content_level smallint NOT NULL GENERATED ALWAYS AS (
+        CASE UPPER(content_rating)
+            WHEN 'ADULT' THEN 2
+            WHEN 'SUGGESTIVE' THEN 1
+            ELSE 0
+        END
+    ) STORED;
+
+ALTER TABLE manga_reports
+    ADD COLUMN content_rating varchar(24) NULL;
+
+UPDATE manga_reports SET content_rating = 'ADULT' WHERE is_nsfw;
+
+ALTER TABLE manga_reports
+    DROP COLUMN is_nsfw;
+
+ALTER TABLE users
+    ADD COLUMN max_content_rating smallint NULL;
+
+UPDATE users
+SET
+    max_content_rating = CASE WHEN show_nsfw THEN 2 ELSE 1 END
+WHERE
+    show_nsfw IS NOT NULL;
+
+ALTER TABLE users
+    DROP COLUMN show_nsfw;
@@ -12,4 +12,8 @@ pub enum AuthError {
     UserNotFound,
     #[error("Incorrect credential")]
     IncorrectCredential,
+    #[error("Account is disabled")]
+    AccountDisabled,
+    #[error("Forbidden")]
+    Forbidden,
 }
@@ -0,0 +1,7 @@
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("Invalid archive: {0}")]
+    InvalidArchive(anyhow::Error),
+    #[error("Unsupported archive version: {0}")]
+    UnsupportedVersion(u32),
+}
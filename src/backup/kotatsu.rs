@@ -0,0 +1,371 @@
+//! Kotatsu's in-app backup: a zip with one JSON array per entry
+//! (`history`, `categories`, `favourites`, `bookmarks`, ...).
+
+use std::{collections::HashSet, io::Cursor, sync::Arc};
+
+use anyhow::Context;
+use serde::Deserialize;
+use zip::ZipArchive;
+
+use crate::model::{
+    Bookmark, Category, Favourite, History, Manga, Tag, UserBookmark, UserFavourite, UserHistory,
+};
+
+use super::{error::BackupError, long_hash_code, read_entry};
+
+const HISTORY_ENTRY: &str = "history";
+const CATEGORIES_ENTRY: &str = "categories";
+const FAVOURITES_ENTRY: &str = "favourites";
+const BOOKMARKS_ENTRY: &str = "bookmarks";
+
+#[derive(Debug)]
+pub struct Backup {
+    pub favourites: UserFavourite,
+    pub history: UserHistory,
+    pub bookmarks: UserBookmark,
+    pub unmatched: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Flag {
+    Bool(bool),
+    Number(u8),
+}
+
+impl Flag {
+    fn as_u8(&self) -> u8 {
+        match self {
+            Flag::Bool(value) => *value as u8,
+            Flag::Number(value) => *value,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TagBackup {
+    title: String,
+    key: String,
+    source: String,
+}
+
+#[derive(Deserialize)]
+struct MangaBackup {
+    id: i64,
+    title: String,
+    alt_title: Option<String>,
+    url: String,
+    public_url: String,
+    #[serde(default = "default_rating")]
+    rating: f32,
+    nsfw: Option<Flag>,
+    content_rating: Option<String>,
+    cover_url: String,
+    large_cover_url: Option<String>,
+    state: Option<String>,
+    author: Option<String>,
+    source: String,
+    #[serde(default)]
+    tags: Vec<TagBackup>,
+}
+
+#[derive(Deserialize)]
+struct HistoryBackup {
+    manga_id: i64,
+    created_at: i64,
+    updated_at: i64,
+    chapter_id: i64,
+    page: i16,
+    scroll: f32,
+    #[serde(default = "default_percent")]
+    percent: f32,
+    #[serde(default = "default_chapters")]
+    chapters: i32,
+    manga: MangaBackup,
+}
+
+#[derive(Deserialize)]
+struct CategoryBackup {
+    category_id: i64,
+    created_at: i64,
+    sort_key: i32,
+    title: String,
+    order: Option<String>,
+    track: Option<Flag>,
+    show_in_lib: Option<Flag>,
+}
+
+#[derive(Deserialize)]
+struct FavouriteBackup {
+    manga_id: i64,
+    category_id: i64,
+    #[serde(default)]
+    sort_key: i32,
+    created_at: i64,
+    manga: MangaBackup,
+}
+
+#[derive(Deserialize)]
+struct BookmarkEntryBackup {
+    manga_id: i64,
+    page_id: i64,
+    chapter_id: i64,
+    page: i32,
+    scroll: i32,
+    image_url: String,
+    created_at: i64,
+    #[serde(default = "default_percent")]
+    percent: f32,
+}
+
+#[derive(Deserialize)]
+struct BookmarkBackup {
+    manga: MangaBackup,
+    bookmarks: Vec<BookmarkEntryBackup>,
+}
+
+fn default_rating() -> f32 {
+    -1.0
+}
+
+fn default_percent() -> f32 {
+    -1.0
+}
+
+fn default_chapters() -> i32 {
+    -1
+}
+
+impl From<MangaBackup> for Manga {
+    fn from(manga: MangaBackup) -> Self {
+        let tags = manga
+            .tags
+            .into_iter()
+            .map(|tag| {
+                Arc::new(Tag {
+                    tag_id: long_hash_code(&format!("{}_{}", tag.key, tag.source)),
+                    title: tag.title,
+                    key: tag.key,
+                    source: tag.source,
+                })
+            })
+            .collect();
+
+        Manga {
+            manga_id: manga.id,
+            title: manga.title,
+            alt_title: manga.alt_title,
+            url: manga.url,
+            public_url: manga.public_url,
+            rating: manga.rating,
+            nsfw: manga.nsfw.map(|nsfw| nsfw.as_u8()),
+            content_rating: manga.content_rating,
+            cover_url: manga.cover_url,
+            large_cover_url: manga.large_cover_url,
+            state: manga.state,
+            author: manga.author,
+            source: manga.source,
+            tags,
+        }
+    }
+}
+
+pub fn read_backup(bytes: &[u8]) -> Result<Backup, BackupError> {
+    let mut zip =
+        ZipArchive::new(Cursor::new(bytes)).map_err(|e| BackupError::InvalidArchive(e.into()))?;
+
+    let history_backup: Vec<HistoryBackup> = parse_entry(&mut zip, HISTORY_ENTRY)?;
+    let categories_backup: Vec<CategoryBackup> = parse_entry(&mut zip, CATEGORIES_ENTRY)?;
+    let favourites_backup: Vec<FavouriteBackup> = parse_entry(&mut zip, FAVOURITES_ENTRY)?;
+    let bookmarks_backup: Vec<BookmarkBackup> = parse_entry(&mut zip, BOOKMARKS_ENTRY)?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut unmatched = Vec::new();
+
+    let history = history_backup
+        .into_iter()
+        .map(|history| History {
+            manga_id: history.manga_id,
+            manga: Arc::new(history.manga.into()),
+            created_at: history.created_at,
+            updated_at: history.updated_at,
+            chapter_id: history.chapter_id,
+            page: history.page,
+            scroll: history.scroll,
+            percent: history.percent,
+            chapters: history.chapters,
+            deleted_at: 0,
+        })
+        .collect();
+
+    let category_ids: HashSet<i64> = categories_backup
+        .iter()
+        .map(|category| category.category_id)
+        .collect();
+    let favourite_categories = categories_backup
+        .into_iter()
+        .map(|category| Category {
+            category_id: category.category_id,
+            created_at: category.created_at,
+            sort_key: category.sort_key,
+            track: category.track.map_or(1, |track| track.as_u8()),
+            show_in_lib: category.show_in_lib.map_or(1, |show| show.as_u8()),
+            deleted_at: 0,
+            title: category.title,
+            order: category.order.unwrap_or_else(|| "NEWEST".to_string()),
+        })
+        .collect();
+
+    let mut favourites = Vec::new();
+    for favourite in favourites_backup {
+        if !category_ids.contains(&favourite.category_id) {
+            unmatched.push(format!(
+                "favourite {}: unknown category {}",
+                favourite.manga.title, favourite.category_id
+            ));
+            continue;
+        }
+
+        favourites.push(Favourite {
+            manga_id: favourite.manga_id,
+            manga: Arc::new(favourite.manga.into()),
+            category_id: favourite.category_id,
+            sort_key: favourite.sort_key,
+            created_at: favourite.created_at,
+            deleted_at: 0,
+        });
+    }
+
+    let mut bookmarks = Vec::new();
+    for bookmark_backup in bookmarks_backup {
+        let manga: Arc<Manga> = Arc::new(bookmark_backup.manga.into());
+        for bookmark in bookmark_backup.bookmarks {
+            bookmarks.push(Bookmark {
+                manga_id: bookmark.manga_id,
+                manga: Arc::clone(&manga),
+                page_id: bookmark.page_id,
+                chapter_id: bookmark.chapter_id,
+                page: bookmark.page,
+                scroll: bookmark.scroll,
+                image_url: bookmark.image_url,
+                created_at: bookmark.created_at,
+                percent: bookmark.percent,
+                deleted_at: 0,
+            });
+        }
+    }
+
+    Ok(Backup {
+        favourites: UserFavourite {
+            favourite_categories,
+            favourites,
+            timestamp: now,
+        },
+        history: UserHistory {
+            history,
+            timestamp: now,
+        },
+        bookmarks: UserBookmark {
+            bookmarks,
+            timestamp: now,
+        },
+        unmatched,
+    })
+}
+
+/// Missing entries are treated as empty, Kotatsu skips sections the user did not select.
+fn parse_entry<T>(zip: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Vec<T>, BackupError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    match read_entry(zip, name)? {
+        Some(bytes) => serde_json::from_slice(&bytes)
+            .with_context(|| format!("parse {}", name))
+            .map_err(BackupError::InvalidArchive),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::read_backup;
+
+    const MANGA: &str = r#"{
+        "id": 10, "title": "Title", "url": "/manga/10", "public_url": "https://localhost/manga/10",
+        "nsfw": true, "cover_url": "https://localhost/cover.jpg", "source": "SOURCE",
+        "tags": [{ "title": "Action", "key": "action", "source": "SOURCE" }]
+    }"#;
+
+    fn create_backup(entries: &[(&str, String)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn can_read_backup() {
+        let bytes = create_backup(&[
+            (
+                "history",
+                format!(
+                    r#"[{{ "manga_id": 10, "created_at": 1, "updated_at": 2, "chapter_id": 3,
+                        "page": 4, "scroll": 0.0, "percent": 0.5, "manga": {} }}]"#,
+                    MANGA
+                ),
+            ),
+            (
+                "categories",
+                r#"[{ "category_id": 1, "created_at": 1, "sort_key": 0, "title": "Reading" }]"#
+                    .to_string(),
+            ),
+            (
+                "favourites",
+                format!(
+                    r#"[{{ "manga_id": 10, "category_id": 1, "created_at": 1, "manga": {} }},
+                        {{ "manga_id": 10, "category_id": 2, "created_at": 1, "manga": {} }}]"#,
+                    MANGA, MANGA
+                ),
+            ),
+            (
+                "bookmarks",
+                format!(
+                    r#"[{{ "manga": {}, "tags": [], "bookmarks": [{{ "manga_id": 10, "page_id": 5,
+                        "chapter_id": 3, "page": 4, "scroll": 0, "image_url": "https://localhost/4.jpg",
+                        "created_at": 1, "percent": 0.5 }}] }}]"#,
+                    MANGA
+                ),
+            ),
+        ]);
+
+        let backup = read_backup(&bytes).unwrap();
+
+        assert_eq!(backup.history.history.len(), 1);
+        assert_eq!(backup.history.history[0].chapters, -1);
+        assert_eq!(backup.history.history[0].manga.nsfw, Some(1));
+        assert_eq!(backup.history.history[0].manga.tags.len(), 1);
+        assert_eq!(backup.favourites.favourite_categories.len(), 1);
+        assert_eq!(backup.favourites.favourites.len(), 1);
+        assert_eq!(backup.bookmarks.bookmarks.len(), 1);
+        assert_eq!(backup.unmatched.len(), 1);
+    }
+
+    #[test]
+    fn can_read_backup_with_missing_entries() {
+        let bytes = create_backup(&[("index", "[]".to_string())]);
+
+        let backup = read_backup(&bytes).unwrap();
+
+        assert!(backup.history.history.is_empty());
+        assert!(backup.favourites.favourites.is_empty());
+    }
+}
@@ -0,0 +1,430 @@
+//! Mihon / Tachiyomi `.tachibk` backups: a gzip-compressed protobuf `Backup` message.
+//!
+//! Sources and manga are mapped the way Kotatsu derives its ids, so importing the same backup
+//! twice updates the same records: the source name is upper-cased with every non alphanumeric
+//! character replaced by `_`, and manga and chapter ids are `long_hash_code(source + url)`.
+//! Backups hold source-relative urls without the source's domain, so the public url is only
+//! kept when it is already absolute.
+
+use std::{collections::HashMap, io::Cursor, sync::Arc};
+
+use anyhow::Context;
+use flate2::read::GzDecoder;
+use prost::Message;
+
+use crate::model::{
+    Category, Favourite, History, Manga, Tag, UserBookmark, UserFavourite, UserHistory,
+};
+
+use super::{MAX_DECOMPRESSED_SIZE, error::BackupError, long_hash_code, read_with_limit};
+
+const DEFAULT_CATEGORY: &str = "Default";
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BackupMessage {
+    #[prost(message, repeated, tag = "1")]
+    pub backup_manga: Vec<BackupManga>,
+    #[prost(message, repeated, tag = "2")]
+    pub backup_categories: Vec<BackupCategory>,
+    #[prost(message, repeated, tag = "101")]
+    pub backup_sources: Vec<BackupSource>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BackupManga {
+    #[prost(int64, tag = "1")]
+    pub source: i64,
+    #[prost(string, tag = "2")]
+    pub url: String,
+    #[prost(string, tag = "3")]
+    pub title: String,
+    #[prost(string, optional, tag = "5")]
+    pub author: Option<String>,
+    #[prost(string, repeated, tag = "7")]
+    pub genre: Vec<String>,
+    #[prost(int32, tag = "8")]
+    pub status: i32,
+    #[prost(string, optional, tag = "9")]
+    pub thumbnail_url: Option<String>,
+    #[prost(int64, tag = "13")]
+    pub date_added: i64,
+    #[prost(message, repeated, tag = "16")]
+    pub chapters: Vec<BackupChapter>,
+    /// `order` of the categories the manga belongs to.
+    #[prost(int64, repeated, tag = "17")]
+    pub categories: Vec<i64>,
+    #[prost(bool, tag = "100")]
+    pub favorite: bool,
+    #[prost(message, repeated, tag = "104")]
+    pub history: Vec<BackupHistory>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BackupCategory {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(int64, tag = "2")]
+    pub order: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BackupChapter {
+    #[prost(string, tag = "1")]
+    pub url: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(bool, tag = "4")]
+    pub read: bool,
+    #[prost(int64, tag = "6")]
+    pub last_page_read: i64,
+    /// `0` is the newest chapter.
+    #[prost(int64, tag = "10")]
+    pub source_order: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BackupHistory {
+    #[prost(string, tag = "1")]
+    pub url: String,
+    #[prost(int64, tag = "2")]
+    pub last_read: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BackupSource {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(int64, tag = "2")]
+    pub source_id: i64,
+}
+
+#[derive(Debug)]
+pub struct Backup {
+    pub favourites: UserFavourite,
+    pub history: UserHistory,
+    pub bookmarks: UserBookmark,
+    pub unmatched: Vec<String>,
+}
+
+pub fn read_backup(bytes: &[u8]) -> Result<Backup, BackupError> {
+    let buffer = decompress(bytes, MAX_DECOMPRESSED_SIZE)?;
+    let message = BackupMessage::decode(buffer.as_slice())
+        .context("decode backup")
+        .map_err(BackupError::InvalidArchive)?;
+
+    Ok(convert(message, chrono::Utc::now().timestamp_millis()))
+}
+
+fn decompress(bytes: &[u8], limit: u64) -> Result<Vec<u8>, BackupError> {
+    read_with_limit(GzDecoder::new(Cursor::new(bytes)), "backup", limit)
+}
+
+/// Kotatsu source name of a Mihon source, e.g. `MangaDex` becomes `MANGADEX`.
+pub fn source_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    name.trim_matches('_').to_string()
+}
+
+/// Mihon `SManga` status to Kotatsu `MangaState`.
+fn manga_state(status: i32) -> Option<String> {
+    let state = match status {
+        1 => "ONGOING",
+        2 | 4 => "FINISHED",
+        5 => "ABANDONED",
+        6 => "PAUSED",
+        _ => return None,
+    };
+
+    Some(state.to_string())
+}
+
+fn convert(message: BackupMessage, now: i64) -> Backup {
+    let sources: HashMap<i64, String> = message
+        .backup_sources
+        .iter()
+        .map(|source| (source.source_id, source_name(&source.name)))
+        .collect();
+
+    let mut categories: HashMap<i64, Category> = message
+        .backup_categories
+        .iter()
+        .map(|category| {
+            (
+                category.order,
+                create_category(&category.name, category.order as i32, now),
+            )
+        })
+        .collect();
+    let mut default_category = None;
+
+    let mut favourites = Vec::new();
+    let mut history = Vec::new();
+    let mut unmatched = Vec::new();
+
+    for backup_manga in message.backup_manga {
+        let source = match sources.get(&backup_manga.source) {
+            Some(source) if !source.is_empty() => source.clone(),
+            _ => {
+                unmatched.push(format!(
+                    "manga {}: unknown source {}",
+                    backup_manga.title, backup_manga.source
+                ));
+                continue;
+            }
+        };
+
+        let manga_id = long_hash_code(&format!("{}{}", source, backup_manga.url));
+        let manga = Arc::new(Manga {
+            manga_id,
+            title: backup_manga.title.clone(),
+            alt_title: None,
+            url: backup_manga.url.clone(),
+            public_url: if backup_manga.url.starts_with("https://")
+                || backup_manga.url.starts_with("http://")
+            {
+                backup_manga.url.clone()
+            } else {
+                String::new()
+            },
+            rating: -1.0,
+            nsfw: None,
+            content_rating: None,
+            cover_url: backup_manga.thumbnail_url.clone().unwrap_or_default(),
+            large_cover_url: None,
+            state: manga_state(backup_manga.status),
+            author: backup_manga.author.clone(),
+            source: source.clone(),
+            tags: backup_manga
+                .genre
+                .iter()
+                .map(|genre| {
+                    let key = genre.to_lowercase();
+                    Arc::new(Tag {
+                        tag_id: long_hash_code(&format!("{}_{}", key, source)),
+                        title: genre.clone(),
+                        key,
+                        source: source.clone(),
+                    })
+                })
+                .collect(),
+        });
+
+        if backup_manga.favorite {
+            let mut category_ids = Vec::new();
+            for order in &backup_manga.categories {
+                match categories.get(order) {
+                    Some(category) => category_ids.push(category.category_id),
+                    None => unmatched.push(format!(
+                        "favourite {}: unknown category {}",
+                        backup_manga.title, order
+                    )),
+                }
+            }
+            if category_ids.is_empty() {
+                let category = default_category
+                    .get_or_insert_with(|| create_category(DEFAULT_CATEGORY, -1, now));
+                category_ids.push(category.category_id);
+            }
+
+            for category_id in category_ids {
+                favourites.push(Favourite {
+                    manga_id,
+                    manga: Arc::clone(&manga),
+                    category_id,
+                    sort_key: 0,
+                    created_at: backup_manga.date_added,
+                    deleted_at: 0,
+                });
+            }
+        }
+
+        let last_read = match backup_manga.history.iter().max_by_key(|h| h.last_read) {
+            Some(last_read) => last_read,
+            None => continue,
+        };
+
+        let mut chapters: Vec<&BackupChapter> = backup_manga.chapters.iter().collect();
+        chapters.sort_by_key(|chapter| std::cmp::Reverse(chapter.source_order));
+
+        let Some(index) = chapters.iter().position(|c| c.url == last_read.url) else {
+            unmatched.push(format!(
+                "history {}: unknown chapter {}",
+                backup_manga.title, last_read.url
+            ));
+            continue;
+        };
+        let chapter = chapters[index];
+        let read_chapters = if chapter.read { index + 1 } else { index };
+
+        history.push(History {
+            manga_id,
+            manga: Arc::clone(&manga),
+            created_at: if backup_manga.date_added > 0 {
+                backup_manga.date_added
+            } else {
+                last_read.last_read
+            },
+            updated_at: last_read.last_read,
+            chapter_id: long_hash_code(&format!("{}{}", source, chapter.url)),
+            page: chapter.last_page_read.clamp(0, i16::MAX as i64) as i16,
+            scroll: 0.0,
+            percent: read_chapters as f32 / chapters.len() as f32,
+            chapters: chapters.len() as i32,
+            deleted_at: 0,
+        });
+    }
+
+    let mut favourite_categories: Vec<Category> = categories.drain().map(|(_, c)| c).collect();
+    favourite_categories.extend(default_category);
+
+    Backup {
+        favourites: UserFavourite {
+            favourite_categories,
+            favourites,
+            timestamp: now,
+        },
+        history: UserHistory {
+            history,
+            timestamp: now,
+        },
+        // Mihon only bookmarks whole chapters, Kotatsu bookmarks are per page.
+        bookmarks: UserBookmark {
+            bookmarks: vec![],
+            timestamp: now,
+        },
+        unmatched,
+    }
+}
+
+fn create_category(name: &str, sort_key: i32, now: i64) -> Category {
+    Category {
+        category_id: long_hash_code(&format!("mihon:{}", name)),
+        created_at: now,
+        sort_key,
+        track: 1,
+        show_in_lib: 1,
+        deleted_at: 0,
+        title: name.to_string(),
+        order: "NEWEST".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression, write::GzEncoder};
+    use prost::Message;
+
+    use crate::backup::long_hash_code;
+
+    use super::{
+        BackupCategory, BackupChapter, BackupHistory, BackupManga, BackupMessage, BackupSource,
+        decompress, read_backup, source_name,
+    };
+
+    fn create_manga(source: i64, url: &str, categories: Vec<i64>) -> BackupManga {
+        BackupManga {
+            source,
+            url: url.to_string(),
+            title: format!("Title {}", url),
+            author: Some("Author".to_string()),
+            genre: vec!["Action".to_string()],
+            status: 2,
+            thumbnail_url: None,
+            date_added: 1000,
+            chapters: vec![
+                BackupChapter {
+                    url: "/chapter/2".to_string(),
+                    name: "Chapter 2".to_string(),
+                    read: false,
+                    last_page_read: 5,
+                    source_order: 0,
+                },
+                BackupChapter {
+                    url: "/chapter/1".to_string(),
+                    name: "Chapter 1".to_string(),
+                    read: true,
+                    last_page_read: 0,
+                    source_order: 1,
+                },
+            ],
+            categories,
+            favorite: true,
+            history: vec![BackupHistory {
+                url: "/chapter/2".to_string(),
+                last_read: 2000,
+            }],
+        }
+    }
+
+    #[test]
+    fn can_map_source_name() {
+        assert_eq!(source_name("MangaDex"), "MANGADEX");
+        assert_eq!(source_name("Comick (Fun)"), "COMICK__FUN");
+    }
+
+    #[test]
+    fn can_read_backup() {
+        let message = BackupMessage {
+            backup_manga: vec![
+                create_manga(1, "/manga/1", vec![0]),
+                create_manga(1, "/manga/2", vec![]),
+                create_manga(2, "/manga/3", vec![]),
+            ],
+            backup_categories: vec![BackupCategory {
+                name: "Reading".to_string(),
+                order: 0,
+            }],
+            backup_sources: vec![BackupSource {
+                name: "MangaDex".to_string(),
+                source_id: 1,
+            }],
+        };
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&message.encode_to_vec()).unwrap();
+        let bytes = encoder.finish().unwrap();
+
+        let backup = read_backup(&bytes).unwrap();
+
+        assert_eq!(backup.unmatched.len(), 1);
+        assert_eq!(backup.favourites.favourite_categories.len(), 2);
+        assert_eq!(backup.favourites.favourites.len(), 2);
+        assert_eq!(backup.history.history.len(), 2);
+
+        let history = &backup.history.history[0];
+        assert_eq!(history.manga_id, long_hash_code("MANGADEX/manga/1"));
+        assert_eq!(history.chapter_id, long_hash_code("MANGADEX/chapter/2"));
+        assert_eq!(history.page, 5);
+        assert_eq!(history.percent, 0.5);
+        assert_eq!(history.manga.state, Some("FINISHED".to_string()));
+        assert_eq!(history.manga.url, "/manga/1");
+        assert!(history.manga.public_url.is_empty());
+    }
+
+    #[test]
+    fn error_when_backup_is_invalid() {
+        assert!(read_backup(b"not a backup").is_err());
+    }
+
+    #[test]
+    fn decompress_should_reject_backups_above_limit() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[0; 16]).unwrap();
+        let bytes = encoder.finish().unwrap();
+
+        assert_eq!(decompress(&bytes, 16).unwrap().len(), 16);
+        assert!(decompress(&bytes, 15).is_err());
+    }
+}
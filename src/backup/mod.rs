@@ -0,0 +1,131 @@
+pub mod error;
+pub mod kotatsu;
+pub mod mihon;
+pub mod rustatsu;
+
+use std::io::Read;
+
+use anyhow::{Context, anyhow};
+use zip::ZipArchive;
+
+use error::BackupError;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
+pub struct ImportSummary {
+    pub categories: usize,
+    pub favourites: usize,
+    pub history: usize,
+    #[serde(default)]
+    pub bookmarks: usize,
+    /// Entries of the archive that could not be imported.
+    #[serde(default)]
+    pub unmatched: Vec<String>,
+}
+
+/// Kotatsu's `String.longHashCode()`, used by the app to derive manga, chapter and tag ids.
+pub fn long_hash_code(value: &str) -> i64 {
+    value
+        .encode_utf16()
+        .fold(1125899906842597i64, |hash, code| {
+            hash.wrapping_mul(31).wrapping_add(code as i64)
+        })
+}
+
+/// Upper bound of a decompressed archive entry or backup, guards against zip and gzip bombs.
+const MAX_DECOMPRESSED_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Reads a single zip entry, `None` when the archive has no such entry.
+fn read_entry<R>(archive: &mut ZipArchive<R>, name: &str) -> Result<Option<Vec<u8>>, BackupError>
+where
+    R: std::io::Read + std::io::Seek,
+{
+    read_entry_with_limit(archive, name, MAX_DECOMPRESSED_SIZE)
+}
+
+fn read_entry_with_limit<R>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+    limit: u64,
+) -> Result<Option<Vec<u8>>, BackupError>
+where
+    R: std::io::Read + std::io::Seek,
+{
+    let file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(BackupError::InvalidArchive(e.into())),
+    };
+    if file.size() > limit {
+        return Err(BackupError::InvalidArchive(anyhow!(
+            "{} is too large",
+            name
+        )));
+    }
+
+    // The declared size is not trusted, the entry is read with the limit as well.
+    read_with_limit(file, name, limit).map(Some)
+}
+
+/// Reads `reader` to the end, failing once it yields more than `limit` bytes rather than
+/// returning a truncated buffer.
+fn read_with_limit<R: std::io::Read>(
+    reader: R,
+    name: &str,
+    limit: u64,
+) -> Result<Vec<u8>, BackupError> {
+    let mut buffer = Vec::new();
+    reader
+        .take(limit + 1)
+        .read_to_end(&mut buffer)
+        .with_context(|| format!("read {}", name))
+        .map_err(BackupError::InvalidArchive)?;
+    if buffer.len() as u64 > limit {
+        return Err(BackupError::InvalidArchive(anyhow!(
+            "{} is too large",
+            name
+        )));
+    }
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::{ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+    use super::{long_hash_code, read_entry_with_limit};
+
+    #[test]
+    fn can_compute_long_hash_code() {
+        assert_eq!(long_hash_code(""), 1125899906842597);
+        assert_eq!(long_hash_code("ab"), 1081989810475738822);
+        assert_eq!(long_hash_code("ab"), long_hash_code("ab"));
+        assert_ne!(long_hash_code("ab"), long_hash_code("ba"));
+    }
+
+    #[test]
+    fn read_entry_should_reject_entries_above_limit() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("entry", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(&[0; 16]).unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(
+            read_entry_with_limit(&mut archive, "entry", 16)
+                .unwrap()
+                .map(|entry| entry.len()),
+            Some(16)
+        );
+        assert!(read_entry_with_limit(&mut archive, "entry", 15).is_err());
+        assert!(
+            read_entry_with_limit(&mut archive, "missing", 16)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
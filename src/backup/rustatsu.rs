@@ -0,0 +1,158 @@
+//! Export archive of a user's library.
+//!
+//! Layout of version 1, a zip with the following JSON entries:
+//!
+//! - `manifest.json`: `{"format": "rustatsu", "version": 1, "exported_at": <unix millis>}`.
+//! - `favourites.json`: categories and favourites with their manga and tags embedded,
+//!   the same document served by `GET /resource/favourites`.
+//! - `history.json`: history with its manga and tags embedded,
+//!   the same document served by `GET /resource/history`.
+//! - `bookmarks.json`: bookmarks with their manga and tags embedded,
+//!   the same document served by `GET /resource/bookmarks`. Optional, absent in older archives.
+//!
+//! Readers must reject archives with a newer `version`.
+
+use std::io::{Cursor, Write};
+
+use anyhow::Context;
+use zip::{CompressionMethod, ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+use crate::model::{UserBookmark, UserFavourite, UserHistory};
+
+use super::{error::BackupError, read_entry};
+
+pub const FORMAT: &str = "rustatsu";
+pub const VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const FAVOURITES_ENTRY: &str = "favourites.json";
+const HISTORY_ENTRY: &str = "history.json";
+const BOOKMARKS_ENTRY: &str = "bookmarks.json";
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: i64,
+}
+
+#[derive(Debug)]
+pub struct Archive {
+    pub favourites: UserFavourite,
+    pub history: UserHistory,
+    pub bookmarks: UserBookmark,
+}
+
+/// Writes the archive to `writer` front to back, so it can be a stream without seeking.
+pub fn write_archive<W: Write>(archive: &Archive, writer: W) -> Result<W, anyhow::Error> {
+    let manifest = Manifest {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+    };
+
+    let mut writer = ZipWriter::new_stream(writer);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    writer.start_file(MANIFEST_ENTRY, options)?;
+    writer.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+
+    writer.start_file(FAVOURITES_ENTRY, options)?;
+    serde_json::to_writer(&mut writer, &archive.favourites).context("write favourites")?;
+
+    writer.start_file(HISTORY_ENTRY, options)?;
+    serde_json::to_writer(&mut writer, &archive.history).context("write history")?;
+
+    writer.start_file(BOOKMARKS_ENTRY, options)?;
+    serde_json::to_writer(&mut writer, &archive.bookmarks).context("write bookmarks")?;
+
+    Ok(writer.finish()?.into_inner())
+}
+
+pub fn read_archive(bytes: &[u8]) -> Result<Archive, BackupError> {
+    let mut zip =
+        ZipArchive::new(Cursor::new(bytes)).map_err(|e| BackupError::InvalidArchive(e.into()))?;
+
+    let manifest: Manifest = parse_entry(&mut zip, MANIFEST_ENTRY)?;
+    if manifest.format != FORMAT {
+        return Err(BackupError::InvalidArchive(anyhow::anyhow!(
+            "unknown format {}",
+            manifest.format
+        )));
+    }
+    if manifest.version > VERSION {
+        return Err(BackupError::UnsupportedVersion(manifest.version));
+    }
+
+    let bookmarks = parse_optional_entry(&mut zip, BOOKMARKS_ENTRY)?.unwrap_or(UserBookmark {
+        bookmarks: vec![],
+        timestamp: manifest.exported_at,
+    });
+
+    Ok(Archive {
+        favourites: parse_entry(&mut zip, FAVOURITES_ENTRY)?,
+        history: parse_entry(&mut zip, HISTORY_ENTRY)?,
+        bookmarks,
+    })
+}
+
+fn parse_entry<T>(zip: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<T, BackupError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    parse_optional_entry(zip, name)?
+        .ok_or_else(|| BackupError::InvalidArchive(anyhow::anyhow!("missing entry {}", name)))
+}
+
+fn parse_optional_entry<T>(
+    zip: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<Option<T>, BackupError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    match read_entry(zip, name)? {
+        Some(bytes) => serde_json::from_slice(&bytes)
+            .with_context(|| format!("parse {}", name))
+            .map_err(BackupError::InvalidArchive),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{UserBookmark, UserFavourite, UserHistory};
+
+    use super::{Archive, read_archive, write_archive};
+
+    #[test]
+    fn can_write_and_read_archive() {
+        let archive = Archive {
+            favourites: UserFavourite {
+                favourite_categories: vec![],
+                favourites: vec![],
+                timestamp: 10,
+            },
+            history: UserHistory {
+                history: vec![],
+                timestamp: 20,
+            },
+            bookmarks: UserBookmark {
+                bookmarks: vec![],
+                timestamp: 30,
+            },
+        };
+
+        let bytes = write_archive(&archive, Vec::new()).unwrap();
+        let result = read_archive(&bytes).unwrap();
+
+        assert_eq!(result.favourites.timestamp, 10);
+        assert_eq!(result.history.timestamp, 20);
+        assert_eq!(result.bookmarks.timestamp, 30);
+    }
+
+    #[test]
+    fn error_when_archive_is_invalid() {
+        assert!(read_archive(b"not a zip").is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use moka::sync::Cache;
+
+/// Entries kept across all users, the least recently used go first.
+const MAX_ENTRIES: u64 = 10_000;
+
+/// Entries not read for this long are dropped, so users who stopped using the API do not
+/// keep memory.
+const TIME_TO_IDLE: Duration = Duration::from_secs(60 * 60);
+
+/// Per-user cache of computed responses. An entry only answers lookups with the `version` it
+/// was stored with, usually the user's latest sync timestamp.
+#[derive(Clone)]
+pub struct UserCache {
+    entries: Cache<(i64, String), (i64, serde_json::Value)>,
+}
+
+impl Default for UserCache {
+    fn default() -> Self {
+        UserCache {
+            entries: Cache::builder()
+                .max_capacity(MAX_ENTRIES)
+                .time_to_idle(TIME_TO_IDLE)
+                .build(),
+        }
+    }
+}
+
+impl UserCache {
+    pub fn get(&self, user_id: i64, version: i64, key: &str) -> Option<serde_json::Value> {
+        self.entries
+            .get(&(user_id, key.to_string()))
+            .filter(|(entry_version, _)| *entry_version == version)
+            .map(|(_, value)| value)
+    }
+
+    pub fn insert(&self, user_id: i64, version: i64, key: String, value: serde_json::Value) {
+        self.entries.insert((user_id, key), (version, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{MAX_ENTRIES, UserCache};
+
+    #[test]
+    fn should_ignore_entries_of_other_versions() {
+        let cache = UserCache::default();
+
+        cache.insert(1, 10, "summary".into(), json!(1));
+        cache.insert(1, 10, "tags".into(), json!(2));
+        assert_eq!(cache.get(1, 10, "summary"), Some(json!(1)));
+        assert_eq!(cache.get(2, 10, "summary"), None);
+
+        assert_eq!(cache.get(1, 11, "summary"), None);
+        cache.insert(1, 11, "summary".into(), json!(3));
+        assert_eq!(cache.get(1, 11, "summary"), Some(json!(3)));
+        assert_eq!(cache.get(1, 11, "tags"), None);
+    }
+
+    #[test]
+    fn should_stay_bounded() {
+        let cache = UserCache::default();
+
+        for key in 0..MAX_ENTRIES * 2 {
+            cache.insert(1, 10, key.to_string(), json!(key));
+        }
+        cache.entries.run_pending_tasks();
+        assert!(cache.entries.entry_count() <= MAX_ENTRIES);
+    }
+}
@@ -6,11 +6,15 @@ use secrecy::{ExposeSecret, SecretString};
 use serde_aux::field_attributes::deserialize_number_from_string;
 use sqlx::postgres::PgConnectOptions;
 
+use crate::model::ContentRating;
+
 #[derive(serde::Deserialize, Debug, Clone)]
 pub struct Config {
     pub application: Application,
     pub database: Database,
     pub jwt: Jwt,
+    #[serde(default)]
+    pub covers: Covers,
 }
 
 #[derive(serde::Deserialize, Debug, Clone)]
@@ -20,6 +24,29 @@ pub struct Application {
     pub host: String,
     pub allow_registration: bool,
     pub run_migration: bool,
+    /// Let requests without a token browse every synced manga through `/manga`, `/tags` and `/sources`.
+    #[serde(default)]
+    pub global_catalog: bool,
+    #[serde(default)]
+    pub manga_metadata_policy: MetadataPolicy,
+    /// Highest content rating listed for users who have not set a preference and for
+    /// anonymous requests.
+    #[serde(default)]
+    pub max_content_rating: ContentRating,
+}
+
+/// How the catalog picks a manga's metadata when users' clients sync different values.
+#[derive(serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataPolicy {
+    /// Whichever client synced last wins.
+    #[default]
+    Overwrite,
+    /// The values of the user whose client most recently reported a change win, so clients
+    /// re-sending stale values do not revert newer ones.
+    MostRecent,
+    /// Each field takes the value reported by the most users.
+    MostCommon,
 }
 
 impl Application {
@@ -28,6 +55,89 @@ impl Application {
     }
 }
 
+/// Cover caching proxy served at `/covers/{manga_id}`.
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct Covers {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub storage: CoverStorage,
+    /// Largest accepted cover in bytes.
+    #[serde(
+        default = "default_cover_max_size",
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub max_size: u64,
+    /// Timeout in seconds when fetching a cover from the source site.
+    #[serde(
+        default = "default_cover_fetch_timeout",
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub fetch_timeout: u64,
+    /// Seconds between runs of the job deleting covers of orphaned manga, `0` disables it.
+    #[serde(
+        default = "default_cover_cleanup_interval",
+        deserialize_with = "deserialize_number_from_string"
+    )]
+    pub cleanup_interval: u64,
+    /// Allows fetching covers from loopback and private network addresses, which are rejected
+    /// by default so cover urls cannot reach internal services.
+    #[serde(default)]
+    pub allow_private_addresses: bool,
+}
+
+impl Default for Covers {
+    fn default() -> Self {
+        Covers {
+            enabled: false,
+            storage: CoverStorage::default(),
+            max_size: default_cover_max_size(),
+            fetch_timeout: default_cover_fetch_timeout(),
+            cleanup_interval: default_cover_cleanup_interval(),
+            allow_private_addresses: false,
+        }
+    }
+}
+
+fn default_cover_max_size() -> u64 {
+    5_242_880 // 5MB
+}
+
+fn default_cover_fetch_timeout() -> u64 {
+    15
+}
+
+fn default_cover_cleanup_interval() -> u64 {
+    86_400
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CoverStorage {
+    Local {
+        path: String,
+    },
+    /// Any S3-compatible store. Credentials missing here are read from the usual `AWS_*`
+    /// environment variables.
+    S3 {
+        bucket: String,
+        region: Option<String>,
+        endpoint: Option<String>,
+        access_key_id: Option<String>,
+        secret_access_key: Option<SecretString>,
+        #[serde(default)]
+        allow_http: bool,
+    },
+}
+
+impl Default for CoverStorage {
+    fn default() -> Self {
+        CoverStorage::Local {
+            path: "covers".into(),
+        }
+    }
+}
+
 #[derive(serde::Deserialize, Debug, Clone)]
 pub struct Jwt {
     pub secret: SecretString,
@@ -88,6 +198,7 @@ impl TryFrom<String> for Environment {
 }
 
 impl Config {
+    #[allow(clippy::result_large_err)]
     pub fn new() -> Result<Self, figment::Error> {
         let base_path =
             std::env::current_dir().expect("Failed to determine the current directory.");
@@ -0,0 +1,118 @@
+use std::{borrow::Cow, sync::Arc};
+
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde_aux::field_attributes::deserialize_option_number_from_string;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::{
+    db::user::{delete_user, get_user_summary_by_id, get_users_summary, update_user_role},
+    error::Error,
+    model::{User, UserSummary},
+    state::SharedAppState,
+};
+
+#[tracing::instrument(name = "[GET] admin/users", skip_all, fields(parameters))]
+pub async fn index(
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<UserQuery>,
+) -> Result<Json<Vec<UserSummary>>, Error> {
+    query.validate().map_err(Error::Validation)?;
+
+    let limit = query.limit.unwrap_or(20);
+    let skip = query.offset.unwrap_or(0) * limit;
+    let search = query.search.filter(|search| !search.is_empty());
+
+    let result = get_users_summary(&app_state.pool, search, limit, skip).await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[GET] admin/users/{id}", skip_all, fields(path.id))]
+pub async fn show(
+    State(app_state): State<SharedAppState>,
+    Path(path): Path<UrlPath>,
+) -> Result<Json<UserSummary>, Error> {
+    let result = get_user_summary_by_id(&app_state.pool, path.id).await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[PATCH] admin/users/{id}", skip_all, fields(path.id))]
+pub async fn update(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Path(path): Path<UrlPath>,
+    axum::extract::Json(request): axum::extract::Json<UpdateUserRequest>,
+) -> Result<Json<UserSummary>, Error> {
+    ensure_not_self(&user, path.id)?;
+
+    update_user_role(
+        &app_state.pool,
+        path.id,
+        request.is_admin,
+        request.is_disabled,
+    )
+    .await?;
+
+    let result = get_user_summary_by_id(&app_state.pool, path.id).await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[DELETE] admin/users/{id}", skip_all, fields(path.id))]
+pub async fn destroy(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Path(path): Path<UrlPath>,
+) -> Result<StatusCode, Error> {
+    ensure_not_self(&user, path.id)?;
+
+    delete_user(&app_state.pool, path.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Admins must not lock themselves out of the admin API.
+fn ensure_not_self(user: &User, user_id: i64) -> Result<(), Error> {
+    if user.id != user_id {
+        return Ok(());
+    }
+
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        "id",
+        ValidationError::new("id_self")
+            .with_message(Cow::from("Admin cannot modify their own account")),
+    );
+
+    Err(Error::Validation(errors))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Validate)]
+pub struct UserQuery {
+    #[validate(length(max = 100))]
+    search: Option<String>,
+
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 0))]
+    offset: Option<i64>,
+
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 0))]
+    limit: Option<i64>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+pub struct UpdateUserRequest {
+    pub is_admin: Option<bool>,
+    pub is_disabled: Option<bool>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct UrlPath {
+    id: i64,
+}
@@ -82,6 +82,10 @@ pub async fn store(
         .map_err(Error::Other)?
         .map_err(|_| Error::Auth(AuthError::IncorrectCredential))?;
 
+    if user.is_disabled {
+        return Err(Error::Auth(AuthError::AccountDisabled));
+    }
+
     let token = spawn_blocking_with_tracing(move || encode_jwt(user.id, &app_state.config.jwt))
         .await
         .context("encode jwt")
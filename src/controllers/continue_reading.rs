@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+};
+use serde_aux::field_attributes::deserialize_option_number_from_string;
+use validator::Validate;
+
+use crate::{
+    db::user_history::get_user_continue_reading,
+    error::Error,
+    model::{History, User},
+    state::SharedAppState,
+};
+
+#[tracing::instrument(name = "[GET] me/continue", skip_all, fields(parameters))]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<ContinueQuery>,
+) -> Result<Json<Vec<History>>, Error> {
+    query.validate().map_err(Error::Validation)?;
+
+    let result =
+        get_user_continue_reading(&app_state.pool, user.id, query.limit.unwrap_or(10)).await?;
+
+    Ok(Json(result))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Validate)]
+pub struct ContinueQuery {
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 1, max = 50))]
+    limit: Option<i64>,
+}
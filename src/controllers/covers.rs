@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    controllers::manga::scope,
+    db::{error::DatabaseError, manga::get_manga_by_id},
+    error::Error,
+    model::User,
+    state::SharedAppState,
+};
+
+#[derive(serde::Deserialize, Debug, Default)]
+pub struct CoverQuery {
+    /// Serve `large_cover_url` when the manga has one.
+    #[serde(default)]
+    pub large: bool,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct UrlPath {
+    manga_id: i64,
+}
+
+#[tracing::instrument(name = "[GET] covers/{manga_id}", skip_all, fields(path.manga_id))]
+pub async fn show(
+    user: Option<Extension<Arc<User>>>,
+    State(app_state): State<SharedAppState>,
+    Path(path): Path<UrlPath>,
+    Query(query): Query<CoverQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Error> {
+    let Some(covers) = &app_state.covers else {
+        return Err(Error::Database(DatabaseError::NotFound));
+    };
+
+    let manga = get_manga_by_id(&app_state.pool, scope(&app_state, user), path.manga_id).await?;
+    let (large, url) = match manga.large_cover_url {
+        Some(url) if query.large && !url.is_empty() => (true, url),
+        _ => (false, manga.cover_url),
+    };
+
+    let if_none_match = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok());
+
+    // Revalidations of a stored cover are answered without reading or fetching it.
+    if let Some(if_none_match) = if_none_match
+        && let Some(etag) = covers
+            .stored_etag(manga.manga_id, large, &url)
+            .await
+            .map_err(Error::Cover)?
+        && matches_etag(if_none_match, &etag)
+    {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers(etag)).into_response());
+    }
+
+    let cover = covers
+        .get(manga.manga_id, large, &url)
+        .await
+        .map_err(Error::Cover)?;
+
+    if if_none_match.is_some_and(|value| matches_etag(value, &cover.etag)) {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers(cover.etag)).into_response());
+    }
+
+    Ok((
+        cache_headers(cover.etag),
+        [(header::CONTENT_TYPE, cover.content_type)],
+        cover.bytes,
+    )
+        .into_response())
+}
+
+// Caches may hold data of the caller's library, so they are kept private.
+fn cache_headers(etag: String) -> [(header::HeaderName, String); 2] {
+    [
+        (header::ETAG, etag),
+        (header::CACHE_CONTROL, "private, max-age=86400".to_string()),
+    ]
+}
+
+fn matches_etag(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .any(|value| value.trim() == etag || value.trim() == "*")
+}
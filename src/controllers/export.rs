@@ -0,0 +1,96 @@
+use std::{
+    io::{self, BufWriter, Write},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use axum::{
+    Extension,
+    body::{Body, Bytes},
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use futures::stream;
+use tokio::sync::mpsc;
+
+use crate::{
+    backup::rustatsu::{Archive, write_archive},
+    db::{
+        user_bookmarks::get_user_bookmarks, user_favourites::get_user_favourites,
+        user_history::get_user_history,
+    },
+    error::Error,
+    model::User,
+    state::SharedAppState,
+    telemetry::spawn_blocking_with_tracing,
+};
+
+#[tracing::instrument(name = "[GET] me/export", skip_all)]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+) -> Result<Response, Error> {
+    let archive = Archive {
+        favourites: get_user_favourites(&app_state.pool, user.id).await?,
+        history: get_user_history(&app_state.pool, user.id).await?,
+        bookmarks: get_user_bookmarks(&app_state.pool, user.id).await?,
+    };
+
+    let (sender, mut receiver) = mpsc::channel(4);
+    spawn_blocking_with_tracing(move || {
+        let writer = BufWriter::with_capacity(
+            CHUNK_SIZE,
+            ChannelWriter {
+                sender: sender.clone(),
+            },
+        );
+        let result = write_archive(&archive, writer)
+            .and_then(|mut writer| writer.flush().context("flush export archive"));
+        if let Err(e) = result {
+            tracing::error!("write export archive: {:?}", e);
+            let _ = sender.blocking_send(Err(io::Error::other(e)));
+        }
+    });
+    let body = Body::from_stream(stream::poll_fn(move |cx| receiver.poll_recv(cx)));
+
+    let filename = format!(
+        "rustatsu-export-{}.zip",
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Size of the chunks the archive is streamed in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Hands everything written to the response body. Writes fail once the client went away,
+/// which stops the archive from being built any further.
+struct ChannelWriter {
+    sender: mpsc::Sender<Result<Bytes, io::Error>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sender
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
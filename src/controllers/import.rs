@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{Extension, Json, body::Bytes, extract::State};
+
+use crate::{
+    backup::{ImportSummary, kotatsu, mihon, rustatsu},
+    db::{
+        user_bookmarks::update_user_bookmarks, user_favourites::update_user_favourites,
+        user_history::update_user_history,
+    },
+    error::Error,
+    model::{User, UserBookmark, UserFavourite, UserHistory},
+    state::SharedAppState,
+    telemetry::spawn_blocking_with_tracing,
+};
+
+#[tracing::instrument(name = "[POST] me/import", skip_all)]
+pub async fn store(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    body: Bytes,
+) -> Result<Json<ImportSummary>, Error> {
+    let archive = spawn_blocking_with_tracing(move || rustatsu::read_archive(&body))
+        .await
+        .context("read import archive")
+        .map_err(Error::Other)?
+        .map_err(Error::Backup)?;
+
+    let summary = merge(
+        &app_state,
+        user.id,
+        archive.favourites,
+        archive.history,
+        archive.bookmarks,
+        Vec::new(),
+    )
+    .await?;
+
+    Ok(Json(summary))
+}
+
+#[tracing::instrument(name = "[POST] me/import/kotatsu-backup", skip_all)]
+pub async fn store_kotatsu(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    body: Bytes,
+) -> Result<Json<ImportSummary>, Error> {
+    let backup = spawn_blocking_with_tracing(move || kotatsu::read_backup(&body))
+        .await
+        .context("read kotatsu backup")
+        .map_err(Error::Other)?
+        .map_err(Error::Backup)?;
+
+    let summary = merge(
+        &app_state,
+        user.id,
+        backup.favourites,
+        backup.history,
+        backup.bookmarks,
+        backup.unmatched,
+    )
+    .await?;
+
+    Ok(Json(summary))
+}
+
+#[tracing::instrument(name = "[POST] me/import/mihon-backup", skip_all)]
+pub async fn store_mihon(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    body: Bytes,
+) -> Result<Json<ImportSummary>, Error> {
+    let backup = spawn_blocking_with_tracing(move || mihon::read_backup(&body))
+        .await
+        .context("read mihon backup")
+        .map_err(Error::Other)?
+        .map_err(Error::Backup)?;
+
+    let summary = merge(
+        &app_state,
+        user.id,
+        backup.favourites,
+        backup.history,
+        backup.bookmarks,
+        backup.unmatched,
+    )
+    .await?;
+
+    Ok(Json(summary))
+}
+
+async fn merge(
+    app_state: &SharedAppState,
+    user_id: i64,
+    user_favourite: UserFavourite,
+    user_history: UserHistory,
+    user_bookmark: UserBookmark,
+    unmatched: Vec<String>,
+) -> Result<ImportSummary, Error> {
+    let summary = ImportSummary {
+        categories: user_favourite.favourite_categories.len(),
+        favourites: user_favourite.favourites.len(),
+        history: user_history.history.len(),
+        bookmarks: user_bookmark.bookmarks.len(),
+        unmatched,
+    };
+
+    if !user_favourite.favourite_categories.is_empty() {
+        update_user_favourites(
+            &app_state.pool,
+            user_id,
+            user_favourite,
+            app_state.config.application.manga_metadata_policy,
+        )
+        .await?;
+    }
+    if !user_history.history.is_empty() {
+        update_user_history(
+            &app_state.pool,
+            user_id,
+            user_history,
+            app_state.config.application.manga_metadata_policy,
+        )
+        .await?;
+    }
+    if !user_bookmark.bookmarks.is_empty() {
+        update_user_bookmarks(
+            &app_state.pool,
+            user_id,
+            user_bookmark,
+            app_state.config.application.manga_metadata_policy,
+        )
+        .await?;
+    }
+
+    Ok(summary)
+}
@@ -0,0 +1,97 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use validator::Validate;
+
+use crate::{
+    db::{
+        error::DatabaseError,
+        manga::get_mangas_by_ids,
+        manga_links::{
+            create_user_link_group, delete_user_link_group, get_user_link_candidates,
+            get_user_link_groups,
+        },
+    },
+    error::Error,
+    linking::suggest_groups,
+    model::{LinkSuggestion, MangaLinkGroup, User},
+    state::SharedAppState,
+};
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Validate)]
+pub struct LinkRequest {
+    #[validate(length(min = 2, max = 50))]
+    pub manga_ids: Vec<i64>,
+}
+
+#[tracing::instrument(name = "[GET] me/links", skip_all)]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+) -> Result<Json<Vec<MangaLinkGroup>>, Error> {
+    let result = get_user_link_groups(&app_state.pool, user.id, None).await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[GET] me/links/suggestions", skip_all)]
+pub async fn suggestions(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+) -> Result<Json<Vec<LinkSuggestion>>, Error> {
+    let candidates = get_user_link_candidates(&app_state.pool, user.id).await?;
+    let groups = suggest_groups(&candidates);
+
+    let manga_ids: Vec<i64> = groups.iter().flatten().copied().collect();
+    let mangas: HashMap<i64, _> = get_mangas_by_ids(&app_state.pool, &manga_ids)
+        .await?
+        .into_iter()
+        .map(|manga| (manga.manga_id, manga))
+        .collect();
+
+    let result = groups
+        .into_iter()
+        .map(|manga_ids| LinkSuggestion {
+            manga: manga_ids
+                .iter()
+                .filter_map(|manga_id| mangas.get(manga_id).cloned())
+                .collect(),
+        })
+        .collect();
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[POST] me/links", skip_all)]
+pub async fn store(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Json(mut payload): Json<LinkRequest>,
+) -> Result<(StatusCode, Json<MangaLinkGroup>), Error> {
+    payload.manga_ids.sort_unstable();
+    payload.manga_ids.dedup();
+    payload.validate().map_err(Error::Validation)?;
+
+    let group_id = create_user_link_group(&app_state.pool, user.id, &payload.manga_ids).await?;
+    let group = get_user_link_groups(&app_state.pool, user.id, Some(group_id))
+        .await?
+        .pop()
+        .ok_or(Error::Database(DatabaseError::NotFound))?;
+
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+#[tracing::instrument(name = "[DELETE] me/links/{group_id}", skip_all, fields(path.group_id))]
+pub async fn destroy(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Path(group_id): Path<i64>,
+) -> Result<StatusCode, Error> {
+    delete_user_link_group(&app_state.pool, user.id, group_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
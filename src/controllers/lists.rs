@@ -0,0 +1,164 @@
+use std::{fmt::Write, sync::Arc};
+
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use uuid::Uuid;
+
+use crate::{
+    db::public_lists::{
+        get_shared_list, get_user_public_lists, publish_category, unpublish_category,
+    },
+    error::Error,
+    model::{PublicList, SharedList, User},
+    state::SharedAppState,
+};
+
+#[derive(serde::Deserialize, Debug, Default)]
+pub struct PublishRequest {
+    /// Hide manga flagged as NSFW from the public list.
+    pub hide_nsfw: Option<bool>,
+}
+
+#[tracing::instrument(name = "[GET] me/lists", skip_all)]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+) -> Result<Json<Vec<PublicList>>, Error> {
+    let result = get_user_public_lists(&app_state.pool, user.id).await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[PUT] me/lists/{category_id}", skip_all, fields(path.category_id))]
+pub async fn update(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Path(category_id): Path<i64>,
+    payload: Option<Json<PublishRequest>>,
+) -> Result<Json<PublicList>, Error> {
+    let Json(payload) = payload.unwrap_or_default();
+    let slug = Uuid::new_v4().simple().to_string();
+
+    let result = publish_category(
+        &app_state.pool,
+        user.id,
+        category_id,
+        &slug,
+        payload.hide_nsfw,
+    )
+    .await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[DELETE] me/lists/{category_id}", skip_all, fields(path.category_id))]
+pub async fn destroy(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Path(category_id): Path<i64>,
+) -> Result<StatusCode, Error> {
+    unpublish_category(&app_state.pool, user.id, category_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[tracing::instrument(name = "[GET] lists/{slug}", skip_all, fields(path.slug))]
+pub async fn show(
+    State(app_state): State<SharedAppState>,
+    Path(slug): Path<String>,
+) -> Result<Json<SharedList>, Error> {
+    let result = get_shared_list(
+        &app_state.pool,
+        &slug,
+        app_state.config.application.max_content_rating,
+    )
+    .await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[GET] lists/{slug}/feed", skip_all, fields(path.slug))]
+pub async fn feed(
+    State(app_state): State<SharedAppState>,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let list = get_shared_list(
+        &app_state.pool,
+        &slug,
+        app_state.config.application.max_content_rating,
+    )
+    .await?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+        atom_feed(&slug, &list),
+    )
+        .into_response())
+}
+
+fn atom_feed(slug: &str, list: &SharedList) -> String {
+    let updated = list.items.iter().map(|i| i.created_at).max().unwrap_or(0);
+
+    let mut feed = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    feed.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    let _ = write!(
+        feed,
+        "<id>urn:rustatsu:list:{}</id><title>{}</title><updated>{}</updated>",
+        escape_xml(slug),
+        escape_xml(&list.title),
+        rfc3339(updated)
+    );
+    let _ = write!(
+        feed,
+        "<author><name>{}</name></author>",
+        escape_xml(list.nickname.as_deref().unwrap_or("rustatsu"))
+    );
+
+    for item in &list.items {
+        let manga = &item.manga;
+        let _ = write!(
+            feed,
+            "<entry><id>urn:rustatsu:manga:{}</id><title>{}</title><updated>{}</updated>",
+            manga.manga_id,
+            escape_xml(&manga.title),
+            rfc3339(item.created_at)
+        );
+        // Manga imported from backups without the source's domain have no public url.
+        if !manga.public_url.is_empty() {
+            let _ = write!(feed, "<link href=\"{}\"/>", escape_xml(&manga.public_url));
+        }
+        if let Some(author) = &manga.author {
+            let _ = write!(feed, "<summary>{}</summary>", escape_xml(author));
+        }
+        feed.push_str("</entry>");
+    }
+
+    feed.push_str("</feed>");
+    feed
+}
+
+fn rfc3339(millis: i64) -> String {
+    DateTime::<Utc>::from_timestamp_millis(millis)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
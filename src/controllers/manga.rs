@@ -1,51 +1,168 @@
+use std::sync::Arc;
+
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Path, Query, State},
 };
 use validator::Validate;
 
 use crate::{
-    db::manga::{get_manga_by_id, get_manga_with_pagination},
+    db::{
+        manga::{
+            MangaScope, MangaSearch, get_manga_by_id, get_manga_with_pagination, search_mangas,
+        },
+        manga_revisions::get_manga_revisions,
+    },
     error::Error,
-    model::Manga,
-    state::SharedAppState,
+    model::{ContentRating, Manga, MangaRevision, Page, User},
+    pagination::{Pagination, invalid_cursor, paginate},
+    state::{AppState, SharedAppState},
 };
 use serde_aux::field_attributes::deserialize_option_number_from_string;
 
 #[tracing::instrument(name = "[GET] manga", skip_all, fields(parameters))]
 pub async fn index(
+    user: Option<Extension<Arc<User>>>,
     State(app_state): State<SharedAppState>,
     Query(pagination): Query<Pagination>,
-) -> Result<Json<Vec<Manga>>, Error> {
+) -> Result<Json<Page<Manga>>, Error> {
     pagination.validate().map_err(Error::Validation)?;
 
-    let limit = pagination.limit.unwrap_or(20);
-    let skip = pagination.offset.unwrap_or(0) * limit;
+    let limit = pagination.limit();
+    let after_id = pagination.position::<i64>()?;
 
-    let result = get_manga_with_pagination(&app_state.pool, limit, skip).await?;
+    let rows = get_manga_with_pagination(
+        &app_state.pool,
+        scope(&app_state, user),
+        after_id,
+        limit + 1,
+    )
+    .await?;
 
-    Ok(Json(result))
+    Ok(Json(paginate(rows, limit, |last| last.manga_id)))
+}
+
+#[tracing::instrument(name = "[GET] manga/search", skip_all, fields(parameters))]
+pub async fn search(
+    user: Option<Extension<Arc<User>>>,
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<Page<Arc<Manga>>>, Error> {
+    query.validate().map_err(Error::Validation)?;
+    query.pagination.validate().map_err(Error::Validation)?;
+
+    // Ranked results have no stable key to seek from, so the cursor holds an offset.
+    let limit = query.pagination.limit();
+    let skip = query.pagination.position::<i64>()?.unwrap_or(0);
+    if skip < 0 {
+        return Err(invalid_cursor());
+    }
+
+    let search = MangaSearch {
+        query: query.q,
+        source: query.source,
+        // Every listed tag must match, so repeated keys would never be satisfied.
+        tags: query.tags.map(|tags| {
+            let mut tags: Vec<String> = tags
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+            tags.sort_unstable();
+            tags.dedup();
+            tags
+        }),
+        state: query.state,
+        nsfw: query.nsfw.map(|nsfw| nsfw == 1),
+        content_rating: query.content_rating,
+    };
+
+    let rows = search_mangas(
+        &app_state.pool,
+        scope(&app_state, user),
+        &search,
+        limit + 1,
+        skip,
+    )
+    .await?;
+
+    Ok(Json(paginate(rows, limit, |_| skip + limit)))
 }
 
 #[tracing::instrument(name = "[GET] manga/{id}", skip_all, fields(path.id))]
 pub async fn show(
+    user: Option<Extension<Arc<User>>>,
     State(app_state): State<SharedAppState>,
     Path(path): Path<UrlPath>,
 ) -> Result<Json<Manga>, Error> {
-    let result = get_manga_by_id(&app_state.pool, path.id).await?;
+    let result = get_manga_by_id(&app_state.pool, scope(&app_state, user), path.id).await?;
 
     Ok(Json(result))
 }
 
+#[tracing::instrument(name = "[GET] manga/{id}/revisions", skip_all, fields(path.id))]
+pub async fn revisions(
+    user: Option<Extension<Arc<User>>>,
+    State(app_state): State<SharedAppState>,
+    Path(path): Path<UrlPath>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Page<MangaRevision>>, Error> {
+    pagination.validate().map_err(Error::Validation)?;
+
+    let limit = pagination.limit();
+    let before_id = pagination.position::<i64>()?;
+
+    // Revisions are only visible for manga the caller can see.
+    get_manga_by_id(&app_state.pool, scope(&app_state, user.clone()), path.id).await?;
+
+    let mut rows = get_manga_revisions(&app_state.pool, path.id, before_id, limit + 1).await?;
+
+    let is_admin = user.as_ref().is_some_and(|user| user.is_admin);
+    let user_id = user.as_ref().map(|user| user.id);
+    if !is_admin {
+        for revision in rows.iter_mut() {
+            if revision.user_id != user_id {
+                revision.user_id = None;
+            }
+        }
+    }
+
+    Ok(Json(paginate(rows, limit, |last| last.id)))
+}
+
+/// Authenticated users only see their own library, anonymous requests (only allowed in global
+/// catalog mode) see every manga. The content rating follows the user's preference or the
+/// server default.
+pub(crate) fn scope(app_state: &AppState, user: Option<Extension<Arc<User>>>) -> MangaScope {
+    let default_max_content_rating = app_state.config.application.max_content_rating;
+
+    match user {
+        Some(Extension(user)) => MangaScope::user(user.id)
+            .max_content_rating(user.max_content_rating(default_max_content_rating)),
+        None => MangaScope::global().max_content_rating(default_max_content_rating),
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Debug, Validate)]
-pub struct Pagination {
-    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
-    #[validate(range(min = 0))]
-    offset: Option<i64>,
+pub struct SearchQuery {
+    #[validate(length(min = 1, max = 100))]
+    q: String,
+
+    source: Option<String>,
+
+    /// Comma separated tag keys.
+    tags: Option<String>,
+
+    state: Option<String>,
 
     #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
-    #[validate(range(min = 0))]
-    limit: Option<i64>,
+    #[validate(range(min = 0, max = 1))]
+    nsfw: Option<u8>,
+
+    content_rating: Option<ContentRating>,
+
+    #[serde(flatten)]
+    pagination: Pagination,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -1,10 +1,189 @@
-use std::sync::Arc;
+use std::{borrow::Cow, sync::Arc};
 
-use axum::{Extension, Json};
+use anyhow::Context;
+use axum::{Extension, Json, extract::State, http::StatusCode};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Deserializer};
+use validator::{Validate, ValidateEmail, ValidateLength, ValidationError, ValidationErrors};
 
-use crate::{error::Error, model::User};
+use crate::{
+    auth::{error::AuthError, verify_password_hash},
+    db::user::{delete_user, get_user_password_by_id, is_email_taken, update_user_profile},
+    error::Error,
+    model::{ContentRating, User},
+    state::SharedAppState,
+    telemetry::spawn_blocking_with_tracing,
+};
+
+#[derive(Deserialize)]
+pub struct UpdateMeRequest {
+    pub email: Option<String>,
+    /// `null` clears the nickname.
+    #[serde(default, deserialize_with = "double_option")]
+    pub nickname: Option<Option<String>>,
+    /// Current password, required when changing email.
+    pub password: Option<SecretString>,
+    /// Opt in or out of contributing favourites to other users' recommendations.
+    pub share_reading_signals: Option<bool>,
+    /// Highest content rating listed in the catalog, search, recommendations and shared lists,
+    /// `null` goes back to the server default.
+    #[serde(default, deserialize_with = "double_option")]
+    pub max_content_rating: Option<Option<ContentRating>>,
+}
+
+/// Tells a field sent as `null` (`Some(None)`) apart from a missing one (`None`).
+fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+impl Validate for UpdateMeRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        if let Some(email) = &self.email {
+            if !email.validate_email() {
+                errors.add(
+                    "email",
+                    ValidationError::new("email_email")
+                        .with_message(Cow::from("Incorrect email format")),
+                );
+            }
+            if !email.validate_length(Some(1), Some(100), None) {
+                errors.add(
+                    "email",
+                    ValidationError::new("email_length")
+                        .with_message(Cow::from("Email length must be between 1 and 100")),
+                );
+            }
+        }
+
+        if let Some(Some(nickname)) = &self.nickname
+            && !nickname.validate_length(Some(1), Some(100), None)
+        {
+            errors.add(
+                "nickname",
+                ValidationError::new("nickname_length")
+                    .with_message(Cow::from("Nickname length must be between 1 and 100")),
+            );
+        }
+
+        if let Some(password) = &self.password
+            && !password
+                .expose_secret()
+                .validate_length(Some(1), Some(32), None)
+        {
+            errors.add(
+                "password",
+                ValidationError::new("password_length")
+                    .with_message(Cow::from("Password length must be between 1 and 32")),
+            );
+        }
+
+        if !errors.errors().is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DeleteMeRequest {
+    pub password: SecretString,
+}
 
 #[tracing::instrument(name = "[GET] me", skip_all)]
 pub async fn index(Extension(user): Extension<Arc<User>>) -> Result<Json<Arc<User>>, Error> {
     Ok(Json(user))
 }
+
+#[tracing::instrument(name = "[PATCH] me", skip_all)]
+pub async fn update(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    axum::extract::Json(request): axum::extract::Json<UpdateMeRequest>,
+) -> Result<Json<User>, Error> {
+    request.validate().map_err(Error::Validation)?;
+
+    let email = request.email.filter(|email| *email != user.email);
+    if let Some(email) = &email {
+        let password = match request.password {
+            Some(password) => password,
+            None => {
+                return Err(Error::Validation(single_error(
+                    "password",
+                    "password_required",
+                    "Password is required to change email",
+                )));
+            }
+        };
+        verify_user_password(&app_state, user.id, password).await?;
+
+        if is_email_taken(&app_state.pool, email, user.id).await? {
+            return Err(Error::Validation(email_taken_error()));
+        }
+    }
+
+    match update_user_profile(
+        &app_state.pool,
+        user.id,
+        email,
+        request.nickname,
+        request.share_reading_signals,
+        request.max_content_rating,
+    )
+    .await?
+    {
+        Some(user) => Ok(Json(user)),
+        None => Err(Error::Validation(email_taken_error())),
+    }
+}
+
+#[tracing::instrument(name = "[DELETE] me", skip_all)]
+pub async fn destroy(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    axum::extract::Json(request): axum::extract::Json<DeleteMeRequest>,
+) -> Result<StatusCode, Error> {
+    verify_user_password(&app_state, user.id, request.password).await?;
+
+    delete_user(&app_state.pool, user.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn verify_user_password(
+    app_state: &SharedAppState,
+    user_id: i64,
+    password: SecretString,
+) -> Result<(), Error> {
+    let hashed_password = get_user_password_by_id(&app_state.pool, user_id).await?;
+
+    spawn_blocking_with_tracing(move || verify_password_hash(hashed_password, password))
+        .await
+        .context("verify password hash")
+        .map_err(Error::Other)?
+        .map_err(|_| Error::Auth(AuthError::IncorrectCredential))
+}
+
+fn email_taken_error() -> ValidationErrors {
+    single_error("email", "email_unique", "Email is already used")
+}
+
+fn single_error(
+    field: &'static str,
+    code: &'static str,
+    message: &'static str,
+) -> ValidationErrors {
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        field,
+        ValidationError::new(code).with_message(Cow::from(message)),
+    );
+
+    errors
+}
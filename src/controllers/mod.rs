@@ -1,5 +1,17 @@
+pub mod admin;
 pub mod auth;
+pub mod continue_reading;
+pub mod covers;
+pub mod export;
 pub mod home;
+pub mod import;
+pub mod links;
+pub mod lists;
 pub mod manga;
 pub mod me;
+pub mod recommendations;
 pub mod resources;
+pub mod sources;
+pub mod stats;
+pub mod tags;
+pub mod timeline;
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+};
+use serde_aux::field_attributes::deserialize_option_number_from_string;
+use validator::Validate;
+
+use crate::{
+    db::recommendations::get_user_recommendations,
+    error::Error,
+    model::{ContentRating, Recommendation, User},
+    state::SharedAppState,
+};
+
+#[tracing::instrument(name = "[GET] me/recommendations", skip_all, fields(parameters))]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<RecommendationQuery>,
+) -> Result<Json<Vec<Recommendation>>, Error> {
+    query.validate().map_err(Error::Validation)?;
+
+    let mut max_content_rating =
+        user.max_content_rating(app_state.config.application.max_content_rating);
+    if !query.include_nsfw.unwrap_or(false) {
+        max_content_rating = max_content_rating.min(ContentRating::Suggestive);
+    }
+
+    let result = get_user_recommendations(
+        &app_state.pool,
+        user.id,
+        max_content_rating,
+        query.limit.unwrap_or(20),
+    )
+    .await?;
+
+    Ok(Json(result))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Validate)]
+pub struct RecommendationQuery {
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 1, max = 50))]
+    limit: Option<i64>,
+
+    /// Ignored when the user's content rating preference excludes adult manga.
+    include_nsfw: Option<bool>,
+}
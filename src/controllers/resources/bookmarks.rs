@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+
+use crate::{
+    db::user_bookmarks::{get_user_bookmarks, update_user_bookmarks},
+    error::Error,
+    model::{User, UserBookmark},
+    state::SharedAppState,
+};
+
+#[tracing::instrument(name = "[GET] resource bookmarks", skip_all)]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+) -> Result<Json<UserBookmark>, Error> {
+    let result = get_user_bookmarks(&app_state.pool, user.id).await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[POST] resource bookmarks", skip_all)]
+pub async fn store(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    axum::extract::Json(user_bookmark): axum::extract::Json<UserBookmark>,
+) -> Result<Json<UserBookmark>, Error> {
+    update_user_bookmarks(
+        &app_state.pool,
+        user.id,
+        user_bookmark,
+        app_state.config.application.manga_metadata_policy,
+    )
+    .await?;
+
+    let result = get_user_bookmarks(&app_state.pool, user.id).await?;
+
+    Ok(Json(result))
+}
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+
+use crate::{
+    db::user_chapters::{get_user_chapters, update_user_chapters},
+    error::Error,
+    model::{User, UserChapterRead},
+    state::SharedAppState,
+};
+
+#[tracing::instrument(name = "[GET] resource chapters", skip_all)]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+) -> Result<Json<UserChapterRead>, Error> {
+    let result = get_user_chapters(&app_state.pool, user.id).await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[POST] resource chapters", skip_all)]
+pub async fn store(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    axum::extract::Json(user_chapter): axum::extract::Json<UserChapterRead>,
+) -> Result<Json<UserChapterRead>, Error> {
+    update_user_chapters(&app_state.pool, user.id, user_chapter).await?;
+
+    let result = get_user_chapters(&app_state.pool, user.id).await?;
+
+    Ok(Json(result))
+}
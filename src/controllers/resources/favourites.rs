@@ -27,7 +27,13 @@ pub async fn store(
     State(app_state): State<SharedAppState>,
     Json(user_favourite): Json<UserFavourite>,
 ) -> Result<axum::Json<UserFavourite>, Error> {
-    update_user_favourites(&app_state.pool, user.id, user_favourite).await?;
+    update_user_favourites(
+        &app_state.pool,
+        user.id,
+        user_favourite,
+        app_state.config.application.manga_metadata_policy,
+    )
+    .await?;
 
     let result = get_user_favourites(&app_state.pool, user.id).await?;
     Ok(Json(result))
@@ -25,7 +25,13 @@ pub async fn store(
     State(app_state): State<SharedAppState>,
     axum::extract::Json(user_history): axum::extract::Json<UserHistory>,
 ) -> Result<Json<UserHistory>, Error> {
-    update_user_history(&app_state.pool, user.id, user_history).await?;
+    update_user_history(
+        &app_state.pool,
+        user.id,
+        user_history,
+        app_state.config.application.manga_metadata_policy,
+    )
+    .await?;
 
     let result = get_user_history(&app_state.pool, user.id).await?;
 
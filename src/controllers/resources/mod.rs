@@ -1,2 +1,7 @@
+pub mod bookmarks;
+pub mod chapters;
 pub mod favourites;
 pub mod history;
+pub mod settings;
+pub mod sources;
+pub mod tracking;
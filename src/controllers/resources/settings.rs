@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+};
+use validator::Validate;
+
+use crate::{
+    db::user_settings::{get_user_settings, update_user_settings},
+    error::Error,
+    model::{User, UserSetting},
+    state::SharedAppState,
+};
+
+#[tracing::instrument(name = "[GET] resource settings", skip_all, fields(parameters))]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<SettingQuery>,
+) -> Result<Json<UserSetting>, Error> {
+    let result = get_user_settings(&app_state.pool, user.id, query.namespace).await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[POST] resource settings", skip_all)]
+pub async fn store(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    axum::extract::Json(user_setting): axum::extract::Json<UserSetting>,
+) -> Result<Json<UserSetting>, Error> {
+    user_setting.validate().map_err(Error::Validation)?;
+
+    update_user_settings(&app_state.pool, user.id, user_setting).await?;
+
+    let result = get_user_settings(&app_state.pool, user.id, None).await?;
+
+    Ok(Json(result))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+pub struct SettingQuery {
+    namespace: Option<String>,
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use validator::Validate;
+
+use crate::{
+    db::user_sources::{get_user_sources, update_user_sources},
+    error::Error,
+    model::{User, UserSource},
+    state::SharedAppState,
+};
+
+#[tracing::instrument(name = "[GET] resource sources", skip_all)]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+) -> Result<Json<UserSource>, Error> {
+    let result = get_user_sources(&app_state.pool, user.id).await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[POST] resource sources", skip_all)]
+pub async fn store(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    axum::extract::Json(user_source): axum::extract::Json<UserSource>,
+) -> Result<Json<UserSource>, Error> {
+    user_source.validate().map_err(Error::Validation)?;
+
+    update_user_sources(&app_state.pool, user.id, user_source).await?;
+
+    let result = get_user_sources(&app_state.pool, user.id).await?;
+
+    Ok(Json(result))
+}
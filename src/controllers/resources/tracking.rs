@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+
+use crate::{
+    db::user_tracks::{get_user_tracks, update_user_tracks},
+    error::Error,
+    model::{User, UserTrack},
+    state::SharedAppState,
+};
+
+#[tracing::instrument(name = "[GET] resource tracking", skip_all)]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+) -> Result<Json<UserTrack>, Error> {
+    let result = get_user_tracks(&app_state.pool, user.id).await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[POST] resource tracking", skip_all)]
+pub async fn store(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    axum::extract::Json(user_track): axum::extract::Json<UserTrack>,
+) -> Result<Json<UserTrack>, Error> {
+    update_user_tracks(
+        &app_state.pool,
+        user.id,
+        user_track,
+        app_state.config.application.manga_metadata_policy,
+    )
+    .await?;
+
+    let result = get_user_tracks(&app_state.pool, user.id).await?;
+
+    Ok(Json(result))
+}
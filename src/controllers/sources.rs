@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+};
+use validator::Validate;
+
+use crate::{
+    db::manga::get_sources_with_count,
+    error::Error,
+    model::{Page, SourceStat, User},
+    pagination::{Pagination, paginate},
+    state::SharedAppState,
+};
+
+use super::manga::scope;
+
+#[tracing::instrument(name = "[GET] sources", skip_all, fields(parameters))]
+pub async fn index(
+    user: Option<Extension<Arc<User>>>,
+    State(app_state): State<SharedAppState>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Page<SourceStat>>, Error> {
+    pagination.validate().map_err(Error::Validation)?;
+
+    let limit = pagination.limit();
+    let after = pagination.position::<(i64, String)>()?;
+
+    let rows =
+        get_sources_with_count(&app_state.pool, scope(&app_state, user), after, limit + 1).await?;
+
+    Ok(Json(paginate(rows, limit, |last| {
+        (last.count, last.source.clone())
+    })))
+}
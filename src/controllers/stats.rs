@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+};
+use serde::Serialize;
+use serde_aux::field_attributes::deserialize_option_number_from_string;
+use validator::Validate;
+
+use crate::{
+    db::stats::{
+        get_user_reading_summary, get_user_stats_version, get_user_top_sources, get_user_top_tags,
+        get_user_weekly_chapters,
+    },
+    error::Error,
+    model::User,
+    state::SharedAppState,
+};
+
+#[tracing::instrument(name = "[GET] me/stats", skip_all, fields(parameters))]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<serde_json::Value>, Error> {
+    query.validate().map_err(Error::Validation)?;
+
+    let result = cached(
+        &app_state,
+        user.id,
+        format!("summary:{:?}:{:?}", query.from, query.to),
+        get_user_reading_summary(&app_state.pool, user.id, query.from, query.to),
+    )
+    .await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[GET] me/stats/weekly", skip_all, fields(parameters))]
+pub async fn weekly(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<serde_json::Value>, Error> {
+    query.validate().map_err(Error::Validation)?;
+
+    let result = cached(
+        &app_state,
+        user.id,
+        format!("weekly:{:?}:{:?}", query.from, query.to),
+        get_user_weekly_chapters(&app_state.pool, user.id, query.from, query.to),
+    )
+    .await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[GET] me/stats/tags", skip_all, fields(parameters))]
+pub async fn tags(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<serde_json::Value>, Error> {
+    query.validate().map_err(Error::Validation)?;
+
+    let limit = query.limit.unwrap_or(10);
+    let result = cached(
+        &app_state,
+        user.id,
+        format!("tags:{:?}:{:?}:{}", query.from, query.to, limit),
+        get_user_top_tags(&app_state.pool, user.id, query.from, query.to, limit),
+    )
+    .await?;
+
+    Ok(Json(result))
+}
+
+#[tracing::instrument(name = "[GET] me/stats/sources", skip_all, fields(parameters))]
+pub async fn sources(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<serde_json::Value>, Error> {
+    query.validate().map_err(Error::Validation)?;
+
+    let limit = query.limit.unwrap_or(10);
+    let result = cached(
+        &app_state,
+        user.id,
+        format!("sources:{:?}:{:?}:{}", query.from, query.to, limit),
+        get_user_top_sources(&app_state.pool, user.id, query.from, query.to, limit),
+    )
+    .await?;
+
+    Ok(Json(result))
+}
+
+/// Serves the result from the stats cache, computing it only when the user synced since.
+async fn cached<T: Serialize>(
+    app_state: &SharedAppState,
+    user_id: i64,
+    key: String,
+    compute: impl Future<Output = Result<T, Error>>,
+) -> Result<serde_json::Value, Error> {
+    let version = get_user_stats_version(&app_state.pool, user_id).await?;
+    if let Some(value) = app_state.stats_cache.get(user_id, version, &key) {
+        return Ok(value);
+    }
+
+    let value = serde_json::to_value(compute.await?).map_err(|e| Error::Other(e.into()))?;
+    app_state
+        .stats_cache
+        .insert(user_id, version, key, value.clone());
+
+    Ok(value)
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Validate)]
+pub struct StatsQuery {
+    /// Inclusive lower bound in milliseconds.
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 0))]
+    from: Option<i64>,
+
+    /// Exclusive upper bound in milliseconds.
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 0))]
+    to: Option<i64>,
+
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 1, max = 100))]
+    limit: Option<i64>,
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use validator::Validate;
+
+use crate::{
+    db::{manga::get_mangas_by_tag, tags::get_tags_with_usage},
+    error::Error,
+    model::{Manga, Page, TagStat, User},
+    pagination::{Pagination, paginate},
+    state::SharedAppState,
+};
+
+use super::manga::scope;
+
+#[tracing::instrument(name = "[GET] tags", skip_all, fields(parameters))]
+pub async fn index(
+    user: Option<Extension<Arc<User>>>,
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<TagQuery>,
+) -> Result<Json<Page<TagStat>>, Error> {
+    query.pagination.validate().map_err(Error::Validation)?;
+
+    let limit = query.pagination.limit();
+    let after = query.pagination.position::<(i64, i64)>()?;
+
+    let rows = get_tags_with_usage(
+        &app_state.pool,
+        scope(&app_state, user),
+        query.source.as_deref(),
+        after,
+        limit + 1,
+    )
+    .await?;
+
+    Ok(Json(paginate(rows, limit, |last| {
+        (last.count, last.tag.tag_id)
+    })))
+}
+
+#[tracing::instrument(name = "[GET] tags/{id}/manga", skip_all, fields(path.id))]
+pub async fn manga(
+    user: Option<Extension<Arc<User>>>,
+    State(app_state): State<SharedAppState>,
+    Path(tag_id): Path<i64>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Page<Arc<Manga>>>, Error> {
+    pagination.validate().map_err(Error::Validation)?;
+
+    let limit = pagination.limit();
+    let after_id = pagination.position::<i64>()?;
+
+    let rows = get_mangas_by_tag(
+        &app_state.pool,
+        scope(&app_state, user),
+        tag_id,
+        after_id,
+        limit + 1,
+    )
+    .await?;
+
+    Ok(Json(paginate(rows, limit, |last| last.manga_id)))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+pub struct TagQuery {
+    source: Option<String>,
+
+    #[serde(flatten)]
+    pagination: Pagination,
+}
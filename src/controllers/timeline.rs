@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+};
+use serde_aux::field_attributes::deserialize_option_number_from_string;
+use validator::Validate;
+
+use crate::{
+    db::user_timeline::get_user_timeline,
+    error::Error,
+    model::{TimelineEntry, User},
+    state::SharedAppState,
+};
+
+#[tracing::instrument(name = "[GET] me/timeline", skip_all, fields(parameters))]
+pub async fn index(
+    Extension(user): Extension<Arc<User>>,
+    State(app_state): State<SharedAppState>,
+    Query(query): Query<TimelineQuery>,
+) -> Result<Json<Vec<TimelineEntry>>, Error> {
+    query.validate().map_err(Error::Validation)?;
+
+    let result = get_user_timeline(
+        &app_state.pool,
+        user.id,
+        query.from,
+        query.to,
+        query.limit.unwrap_or(50),
+    )
+    .await?;
+
+    Ok(Json(result))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Validate)]
+pub struct TimelineQuery {
+    /// Inclusive lower bound in milliseconds.
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 0))]
+    from: Option<i64>,
+
+    /// Exclusive upper bound in milliseconds.
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 0))]
+    to: Option<i64>,
+
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 1, max = 500))]
+    limit: Option<i64>,
+}
@@ -0,0 +1,11 @@
+#[derive(Debug, thiserror::Error)]
+pub enum CoverError {
+    #[error("Unable to fetch cover: {0}")]
+    Fetch(anyhow::Error),
+    #[error("Cover is not a supported image")]
+    InvalidImage,
+    #[error("Cover exceeds {0} bytes")]
+    TooLarge(u64),
+    #[error("Cover storage error: {0}")]
+    Storage(#[from] object_store::Error),
+}
@@ -0,0 +1,344 @@
+pub mod error;
+pub mod resolver;
+
+use std::{collections::HashMap, net::IpAddr, sync::Arc, time::Duration};
+
+use anyhow::Context;
+use axum::body::Bytes;
+use futures::TryStreamExt;
+use object_store::{
+    ObjectStore, ObjectStoreExt, aws::AmazonS3Builder, local::LocalFileSystem, path::Path,
+};
+use secrecy::ExposeSecret;
+use sqlx::PgPool;
+
+use crate::{
+    backup::long_hash_code,
+    config::{CoverStorage, Covers},
+    db::manga::get_existing_manga_ids,
+    error::Error,
+};
+
+use error::CoverError;
+use resolver::{PublicResolver, is_public};
+
+/// Covers fetched from source sites, stored as `{manga_id}/{cover|large}/{long_hash_code(url)}`
+/// so a changed cover url is fetched again.
+#[derive(Clone)]
+pub struct CoverCache {
+    store: Arc<dyn ObjectStore>,
+    client: reqwest::Client,
+    max_size: u64,
+    allow_private_addresses: bool,
+}
+
+pub struct Cover {
+    pub bytes: Bytes,
+    pub content_type: &'static str,
+    pub etag: String,
+}
+
+impl CoverCache {
+    pub fn new(config: &Covers) -> Result<Self, anyhow::Error> {
+        let store: Arc<dyn ObjectStore> = match &config.storage {
+            CoverStorage::Local { path } => {
+                std::fs::create_dir_all(path)
+                    .with_context(|| format!("create cover directory {}", path))?;
+
+                Arc::new(LocalFileSystem::new_with_prefix(path)?)
+            }
+            CoverStorage::S3 {
+                bucket,
+                region,
+                endpoint,
+                access_key_id,
+                secret_access_key,
+                allow_http,
+            } => {
+                let mut builder = AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .with_allow_http(*allow_http);
+                if let Some(region) = region {
+                    builder = builder.with_region(region);
+                }
+                if let Some(endpoint) = endpoint {
+                    builder = builder.with_endpoint(endpoint);
+                }
+                if let Some(access_key_id) = access_key_id {
+                    builder = builder.with_access_key_id(access_key_id);
+                }
+                if let Some(secret_access_key) = secret_access_key {
+                    builder = builder.with_secret_access_key(secret_access_key.expose_secret());
+                }
+
+                Arc::new(builder.build()?)
+            }
+        };
+
+        // Redirects are not followed, their target would skip the checks in `fetch`.
+        let mut client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.fetch_timeout))
+            .redirect(reqwest::redirect::Policy::none());
+        if !config.allow_private_addresses {
+            client = client.dns_resolver(Arc::new(PublicResolver));
+        }
+
+        Ok(CoverCache {
+            store,
+            client: client.build()?,
+            max_size: config.max_size,
+            allow_private_addresses: config.allow_private_addresses,
+        })
+    }
+
+    /// Returns the cached cover, fetching and storing it first on a miss.
+    /// `large` tells the two cover sizes of a manga apart, each keeps its own stored cover.
+    #[tracing::instrument(name = "get cover", skip(self))]
+    pub async fn get(&self, manga_id: i64, large: bool, url: &str) -> Result<Cover, CoverError> {
+        let prefix = prefix(manga_id, large);
+        let location = prefix.clone().join(long_hash_code(url).to_string());
+
+        match self.store.get(&location).await {
+            Ok(result) => {
+                let etag = etag(&location, result.meta.e_tag.as_deref(), result.meta.size);
+                let bytes = result.bytes().await?;
+                // Only images that passed validation are stored.
+                let content_type = sniff_image(&bytes).ok_or(CoverError::InvalidImage)?;
+
+                return Ok(Cover {
+                    bytes,
+                    content_type,
+                    etag,
+                });
+            }
+            Err(object_store::Error::NotFound { .. }) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let bytes = self.fetch(url).await?;
+        let content_type = sniff_image(&bytes).ok_or(CoverError::InvalidImage)?;
+        let result = self.store.put(&location, bytes.clone().into()).await?;
+
+        // Drop covers stored for previous urls of this manga and size.
+        let stale: Vec<Path> = self
+            .store
+            .list(Some(&prefix))
+            .map_ok(|meta| meta.location)
+            .try_filter(|other| futures::future::ready(*other != location))
+            .try_collect()
+            .await?;
+        for other in stale {
+            self.store.delete(&other).await?;
+        }
+
+        Ok(Cover {
+            etag: etag(&location, result.e_tag.as_deref(), bytes.len() as u64),
+            bytes,
+            content_type,
+        })
+    }
+
+    /// ETag of the stored cover without reading it, `None` when it was not fetched yet.
+    #[tracing::instrument(name = "get cover etag", skip(self))]
+    pub async fn stored_etag(
+        &self,
+        manga_id: i64,
+        large: bool,
+        url: &str,
+    ) -> Result<Option<String>, CoverError> {
+        let location = prefix(manga_id, large).join(long_hash_code(url).to_string());
+
+        match self.store.head(&location).await {
+            Ok(meta) => Ok(Some(etag(&location, meta.e_tag.as_deref(), meta.size))),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn fetch(&self, url: &str) -> Result<Bytes, CoverError> {
+        let url = reqwest::Url::parse(url)
+            .with_context(|| format!("parse url {}", url))
+            .map_err(CoverError::Fetch)?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(CoverError::Fetch(anyhow::anyhow!(
+                "unsupported scheme {}",
+                url.scheme()
+            )));
+        }
+        // Hostnames are checked by the resolver, which is not consulted for ip addresses.
+        let ip = url
+            .host_str()
+            .and_then(|host| host.trim_matches(['[', ']']).parse::<IpAddr>().ok());
+        if let Some(ip) = ip
+            && !self.allow_private_addresses
+            && !is_public(ip)
+        {
+            return Err(CoverError::Fetch(anyhow::anyhow!(
+                "address {} is not public",
+                ip
+            )));
+        }
+
+        let mut response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| CoverError::Fetch(e.into()))?;
+
+        let is_image = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("image/"));
+        if !is_image {
+            return Err(CoverError::InvalidImage);
+        }
+        if response
+            .content_length()
+            .is_some_and(|length| length > self.max_size)
+        {
+            return Err(CoverError::TooLarge(self.max_size));
+        }
+
+        // The announced length may be missing or wrong, so the cap is also enforced on the body.
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| CoverError::Fetch(e.into()))?
+        {
+            if (bytes.len() + chunk.len()) as u64 > self.max_size {
+                return Err(CoverError::TooLarge(self.max_size));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok(bytes.into())
+    }
+
+    /// Deletes stored covers whose manga no longer exists, returns the number of deleted covers.
+    #[tracing::instrument(name = "cleanup covers", skip_all)]
+    pub async fn cleanup(&self, pool: &PgPool) -> Result<usize, Error> {
+        let objects: Vec<Path> = self
+            .store
+            .list(None)
+            .map_ok(|meta| meta.location)
+            .try_collect()
+            .await
+            .map_err(|e| Error::Cover(e.into()))?;
+
+        let mut covers: HashMap<i64, Vec<Path>> = HashMap::new();
+        let mut orphaned = Vec::new();
+        for location in objects {
+            let manga_id = location
+                .parts()
+                .next()
+                .and_then(|part| part.as_ref().parse::<i64>().ok());
+            match manga_id {
+                Some(manga_id) => covers.entry(manga_id).or_default().push(location),
+                None => orphaned.push(location),
+            }
+        }
+
+        let manga_ids: Vec<i64> = covers.keys().copied().collect();
+        for manga_id in get_existing_manga_ids(pool, &manga_ids).await? {
+            covers.remove(&manga_id);
+        }
+        orphaned.extend(covers.into_values().flatten());
+
+        for location in &orphaned {
+            self.store
+                .delete(location)
+                .await
+                .map_err(|e| Error::Cover(e.into()))?;
+        }
+
+        Ok(orphaned.len())
+    }
+}
+
+/// Periodically runs [`CoverCache::cleanup`].
+pub async fn cleanup_job(covers: CoverCache, pool: PgPool, interval: Duration) {
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        interval.tick().await;
+
+        match covers.cleanup(&pool).await {
+            Ok(deleted) => tracing::info!(deleted, "Deleted covers of orphaned manga"),
+            Err(e) => tracing::error!(err.msg = %e, err.details = ?e, "Cover cleanup failed"),
+        }
+    }
+}
+
+fn prefix(manga_id: i64, large: bool) -> Path {
+    Path::from(format!(
+        "{}/{}",
+        manga_id,
+        if large { "large" } else { "cover" }
+    ))
+}
+
+fn etag(location: &Path, e_tag: Option<&str>, size: u64) -> String {
+    match e_tag {
+        Some(e_tag) => format!("\"{}\"", e_tag.trim_matches('"')),
+        None => format!("\"{}-{}\"", long_hash_code(location.as_ref()), size),
+    }
+}
+
+/// Content type of the image from its magic bytes, `None` for anything that is not a
+/// supported image.
+pub fn sniff_image(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some("image/png"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'E',
+            b'B',
+            b'P',
+            ..,
+        ] => Some("image/webp"),
+        [
+            _,
+            _,
+            _,
+            _,
+            b'f',
+            b't',
+            b'y',
+            b'p',
+            b'a',
+            b'v',
+            b'i',
+            b'f',
+            ..,
+        ] => Some("image/avif"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sniff_image;
+
+    #[test]
+    fn sniff_image_should_detect_supported_formats() {
+        assert_eq!(sniff_image(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(sniff_image(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(sniff_image(b"GIF89a"), Some("image/gif"));
+        assert_eq!(sniff_image(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_image(b"<html></html>"), None);
+        assert_eq!(sniff_image(&[]), None);
+    }
+}
@@ -1,36 +1,91 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use futures::TryStreamExt;
 use sqlx::{PgPool, Postgres, QueryBuilder, Row};
 
 use crate::{
+    config::MetadataPolicy,
     error::Error,
-    model::{Manga, MangaTag, Tag},
+    model::{ContentRating, Manga, MangaTag, SourceStat, Tag},
 };
 
-use super::{PostgresTransaction, error::DatabaseError};
+use super::{
+    PostgresTransaction,
+    error::DatabaseError,
+    manga_revisions::{
+        MangaMetadata, diff_manga_metadata, get_manga_metadata, insert_manga_reports,
+        insert_manga_revisions, resolve_manga_metadata,
+    },
+    tags::delete_orphaned_tags,
+};
+
+/// Which part of the catalog a query may return.
+#[derive(Clone, Copy, Debug)]
+pub struct MangaScope {
+    /// Only manga in this user's history or favourites, every manga when `None`.
+    pub user_id: Option<i64>,
+    /// Highest content rating included.
+    pub max_content_rating: ContentRating,
+}
+
+impl MangaScope {
+    pub fn global() -> Self {
+        MangaScope {
+            user_id: None,
+            max_content_rating: ContentRating::Adult,
+        }
+    }
+
+    pub fn user(user_id: i64) -> Self {
+        MangaScope {
+            user_id: Some(user_id),
+            max_content_rating: ContentRating::Adult,
+        }
+    }
+
+    pub fn max_content_rating(mut self, max_content_rating: ContentRating) -> Self {
+        self.max_content_rating = max_content_rating;
+        self
+    }
+}
 
 #[tracing::instrument(name = "get manga with pagination", skip_all)]
 pub async fn get_manga_with_pagination(
     pool: &PgPool,
+    scope: MangaScope,
+    after_id: Option<i64>,
     limit: i64,
-    skip: i64,
 ) -> Result<Vec<Manga>, Error> {
     let manga_raw = sqlx::query!(
         r#"
         SELECT
             id, title, alt_title,
             url, public_url, rating,
-            is_nsfw, cover_url, large_cover_url,
+            content_rating, content_level, cover_url, large_cover_url,
             state, author, source
         FROM
             mangas
+        WHERE
+            ($2::bigint IS NULL OR id > $2)
+            AND (
+                $3::bigint IS NULL
+                OR EXISTS (
+                    SELECT 1 FROM history
+                    WHERE history.manga_id = mangas.id AND history.user_id = $3 AND history.deleted_at = 0
+                )
+                OR EXISTS (
+                    SELECT 1 FROM favourites
+                    WHERE favourites.manga_id = mangas.id AND favourites.user_id = $3 AND favourites.deleted_at = 0
+                )
+            )
+            AND content_level <= $4
         ORDER BY id
         LIMIT $1
-        OFFSET $2
     "#,
         limit,
-        skip
+        after_id,
+        scope.user_id,
+        scope.max_content_rating.level()
     )
     .fetch_all(pool)
     .await
@@ -92,12 +147,10 @@ pub async fn get_manga_with_pagination(
             url: manga.url,
             public_url: manga.public_url,
             rating: manga.rating,
-            nsfw: if manga.is_nsfw { Some(1) } else { Some(0) },
-            content_rating: if manga.is_nsfw {
-                Some("ADULT".to_string())
-            } else {
-                None
-            },
+            nsfw: Some(u8::from(
+                manga.content_level == ContentRating::Adult.level(),
+            )),
+            content_rating: manga.content_rating,
             cover_url: manga.cover_url,
             large_cover_url: manga.large_cover_url,
             state: manga.state,
@@ -110,21 +163,235 @@ pub async fn get_manga_with_pagination(
     Ok(mangas)
 }
 
+/// Sources with their number of manga, most used first. `after` is the `(count, source)` of
+/// the last source of the previous page.
+#[tracing::instrument(name = "get sources with count", skip_all)]
+pub async fn get_sources_with_count(
+    pool: &PgPool,
+    scope: MangaScope,
+    after: Option<(i64, String)>,
+    limit: i64,
+) -> Result<Vec<SourceStat>, Error> {
+    let (after_count, after_source) = after.unzip();
+
+    let sources = sqlx::query_as!(
+        SourceStat,
+        r#"
+        SELECT
+            source,
+            COUNT(*) AS "count!"
+        FROM
+            mangas
+        WHERE
+            (
+                $1::bigint IS NULL
+                OR EXISTS (
+                    SELECT 1 FROM history
+                    WHERE history.manga_id = mangas.id AND history.user_id = $1 AND history.deleted_at = 0
+                )
+                OR EXISTS (
+                    SELECT 1 FROM favourites
+                    WHERE favourites.manga_id = mangas.id AND favourites.user_id = $1 AND favourites.deleted_at = 0
+                )
+            )
+            AND content_level <= $5
+        GROUP BY source
+        HAVING
+            $2::bigint IS NULL
+            OR COUNT(*) < $2
+            OR (COUNT(*) = $2 AND source > $3)
+        ORDER BY 2 DESC, source
+        LIMIT $4
+    "#,
+        scope.user_id,
+        after_count,
+        after_source,
+        limit,
+        scope.max_content_rating.level()
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(sources)
+}
+
+/// Manga having the tag, ordered by id.
+#[tracing::instrument(name = "get mangas by tag", skip_all, fields(tag_id))]
+pub async fn get_mangas_by_tag(
+    pool: &PgPool,
+    scope: MangaScope,
+    tag_id: i64,
+    after_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<Arc<Manga>>, Error> {
+    let manga_ids = sqlx::query_scalar!(
+        r#"
+        SELECT
+            manga_tags.manga_id
+        FROM
+            manga_tags
+        INNER JOIN
+            mangas ON mangas.id = manga_tags.manga_id
+        WHERE
+            manga_tags.tag_id = $1
+            AND ($2::bigint IS NULL OR manga_tags.manga_id > $2)
+            AND (
+                $3::bigint IS NULL
+                OR EXISTS (
+                    SELECT 1 FROM history
+                    WHERE history.manga_id = manga_tags.manga_id AND history.user_id = $3 AND history.deleted_at = 0
+                )
+                OR EXISTS (
+                    SELECT 1 FROM favourites
+                    WHERE favourites.manga_id = manga_tags.manga_id AND favourites.user_id = $3 AND favourites.deleted_at = 0
+                )
+            )
+            AND mangas.content_level <= $5
+        ORDER BY manga_tags.manga_id
+        LIMIT $4
+    "#,
+        tag_id,
+        after_id,
+        scope.user_id,
+        limit,
+        scope.max_content_rating.level()
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let mut mangas = get_mangas_by_ids(pool, &manga_ids).await?;
+    mangas.sort_by_key(|manga| manga.manga_id);
+
+    Ok(mangas)
+}
+
+/// Filters for [`search_mangas`].
+#[derive(Debug)]
+pub struct MangaSearch {
+    pub query: String,
+    pub source: Option<String>,
+    /// Tag keys the manga must all have.
+    pub tags: Option<Vec<String>>,
+    pub state: Option<String>,
+    pub nsfw: Option<bool>,
+    pub content_rating: Option<ContentRating>,
+}
+
+/// Full-text and trigram search over title, alt title and author, best match first.
+#[tracing::instrument(name = "search mangas", skip_all)]
+pub async fn search_mangas(
+    pool: &PgPool,
+    scope: MangaScope,
+    search: &MangaSearch,
+    limit: i64,
+    skip: i64,
+) -> Result<Vec<Arc<Manga>>, Error> {
+    let manga_ids = sqlx::query_scalar!(
+        r#"
+        SELECT
+            mangas.id
+        FROM
+            mangas,
+            websearch_to_tsquery('simple', $1) AS query
+        WHERE
+            (
+                mangas.search_vector @@ query
+                OR $1 <% mangas.title
+                OR $1 <% mangas.alt_title
+                OR $1 <% mangas.author
+            )
+            AND ($2::text IS NULL OR mangas.source = $2)
+            AND ($3::text IS NULL OR mangas.state = $3)
+            AND ($4::bool IS NULL OR (mangas.content_level = 2) = $4)
+            AND ($10::smallint IS NULL OR mangas.content_level = $10)
+            AND (
+                $5::text[] IS NULL
+                OR (
+                    SELECT COUNT(DISTINCT tags."key")
+                    FROM manga_tags
+                    INNER JOIN tags ON tags.id = manga_tags.tag_id
+                    WHERE manga_tags.manga_id = mangas.id AND tags."key" = ANY($5)
+                ) = cardinality($5)
+            )
+            AND (
+                $6::bigint IS NULL
+                OR EXISTS (
+                    SELECT 1 FROM history
+                    WHERE history.manga_id = mangas.id AND history.user_id = $6 AND history.deleted_at = 0
+                )
+                OR EXISTS (
+                    SELECT 1 FROM favourites
+                    WHERE favourites.manga_id = mangas.id AND favourites.user_id = $6 AND favourites.deleted_at = 0
+                )
+            )
+            AND mangas.content_level <= $9
+        ORDER BY
+            ts_rank(mangas.search_vector, query)
+            + GREATEST(
+                word_similarity($1, mangas.title),
+                COALESCE(word_similarity($1, mangas.alt_title), 0),
+                COALESCE(word_similarity($1, mangas.author), 0)
+            ) DESC,
+            mangas.id
+        LIMIT $7
+        OFFSET $8
+    "#,
+        search.query,
+        search.source,
+        search.state,
+        search.nsfw,
+        search.tags.as_deref(),
+        scope.user_id,
+        limit,
+        skip,
+        scope.max_content_rating.level(),
+        search.content_rating.map(ContentRating::level)
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let mut mangas = get_mangas_by_ids(pool, &manga_ids).await?;
+    mangas.sort_by_key(|manga| manga_ids.iter().position(|id| *id == manga.manga_id));
+
+    Ok(mangas)
+}
+
 #[tracing::instrument(name = "get manga by id", skip_all, fields(manga_id))]
-pub async fn get_manga_by_id(pool: &PgPool, manga_id: i64) -> Result<Manga, Error> {
+pub async fn get_manga_by_id(
+    pool: &PgPool,
+    scope: MangaScope,
+    manga_id: i64,
+) -> Result<Manga, Error> {
     let manga_raw = match sqlx::query!(
         r#"
         SELECT
             id, title, alt_title,
             url, public_url, rating,
-            is_nsfw, cover_url, large_cover_url,
+            content_rating, content_level, cover_url, large_cover_url,
             state, author, source
         FROM
             mangas
         WHERE
-            id = $1;
+            id = $1
+            AND (
+                $2::bigint IS NULL
+                OR EXISTS (
+                    SELECT 1 FROM history
+                    WHERE history.manga_id = mangas.id AND history.user_id = $2 AND history.deleted_at = 0
+                )
+                OR EXISTS (
+                    SELECT 1 FROM favourites
+                    WHERE favourites.manga_id = mangas.id AND favourites.user_id = $2 AND favourites.deleted_at = 0
+                )
+            )
+            AND content_level <= $3;
     "#,
-        manga_id
+        manga_id,
+        scope.user_id,
+        scope.max_content_rating.level()
     )
     .fetch_optional(pool)
     .await
@@ -168,12 +435,10 @@ pub async fn get_manga_by_id(pool: &PgPool, manga_id: i64) -> Result<Manga, Erro
         url: manga_raw.url,
         public_url: manga_raw.public_url,
         rating: manga_raw.rating,
-        nsfw: if manga_raw.is_nsfw { Some(1) } else { Some(0) },
-        content_rating: if manga_raw.is_nsfw {
-            Some("ADULT".to_string())
-        } else {
-            None
-        },
+        nsfw: Some(u8::from(
+            manga_raw.content_level == ContentRating::Adult.level(),
+        )),
+        content_rating: manga_raw.content_rating,
         cover_url: manga_raw.cover_url,
         large_cover_url: manga_raw.large_cover_url,
         state: manga_raw.state,
@@ -183,52 +448,143 @@ pub async fn get_manga_by_id(pool: &PgPool, manga_id: i64) -> Result<Manga, Erro
     })
 }
 
-pub async fn insert_mangas(tx: &mut PostgresTransaction, data: &[Arc<Manga>]) -> Result<(), Error> {
+/// Loads manga with their tags, in no particular order. Unknown ids are skipped.
+#[tracing::instrument(name = "get mangas by ids", skip_all)]
+pub async fn get_mangas_by_ids(pool: &PgPool, manga_ids: &[i64]) -> Result<Vec<Arc<Manga>>, Error> {
+    if manga_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut tag_stream = sqlx::query!(
+        r#"
+        SELECT
+            manga_tags.manga_id, tags.id, tags.title, tags."key", tags.source
+        FROM
+            manga_tags
+        INNER JOIN
+            tags ON manga_tags.tag_id = tags.id
+        WHERE
+            manga_tags.manga_id = ANY($1);
+    "#,
+        manga_ids
+    )
+    .fetch(pool);
+
+    let mut manga_tags = Vec::new();
+    while let Some(row) = tag_stream
+        .try_next()
+        .await
+        .map_err(DatabaseError::DatabaseError)?
+    {
+        manga_tags.push(MangaTag {
+            manga_id: row.manga_id,
+            tag: Arc::new(Tag {
+                tag_id: row.id,
+                title: row.title,
+                key: row.key,
+                source: row.source,
+            }),
+        });
+    }
+
+    let mangas = sqlx::query!(
+        r#"
+        SELECT
+            id, title, alt_title,
+            url, public_url, rating,
+            content_rating, content_level, cover_url, large_cover_url,
+            state, author, source
+        FROM
+            mangas
+        WHERE
+            id = ANY($1);
+    "#,
+        manga_ids
+    )
+    .map(|manga| {
+        let tags = manga_tags
+            .iter()
+            .filter(|f| f.manga_id == manga.id)
+            .map(|m| Arc::clone(&m.tag))
+            .collect();
+
+        Arc::new(Manga {
+            manga_id: manga.id,
+            title: manga.title,
+            alt_title: manga.alt_title,
+            url: manga.url,
+            public_url: manga.public_url,
+            rating: manga.rating,
+            nsfw: Some(u8::from(
+                manga.content_level == ContentRating::Adult.level(),
+            )),
+            content_rating: manga.content_rating,
+            cover_url: manga.cover_url,
+            large_cover_url: manga.large_cover_url,
+            state: manga.state,
+            author: manga.author,
+            source: manga.source,
+            tags,
+        })
+    })
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(mangas)
+}
+
+/// Upserts the manga, picking their metadata according to `policy` and recording a revision
+/// for every manga whose metadata changed. `user_id` is the user whose sync provided `data`.
+#[tracing::instrument(name = "insert mangas", skip_all)]
+pub async fn insert_mangas(
+    tx: &mut PostgresTransaction,
+    data: &[Arc<Manga>],
+    user_id: Option<i64>,
+    policy: MetadataPolicy,
+) -> Result<(), Error> {
     for batch in data.chunks(100) {
+        let manga_ids: Vec<i64> = batch.iter().map(|manga| manga.manga_id).collect();
+        let reported: Vec<(i64, MangaMetadata)> = batch
+            .iter()
+            .map(|manga| (manga.manga_id, MangaMetadata::from(manga.as_ref())))
+            .collect();
+
+        let current = get_manga_metadata(tx, &manga_ids).await?;
+
+        if let Some(user_id) = user_id {
+            insert_manga_reports(tx, user_id, &reported).await?;
+        }
+
+        let mut resolved: HashMap<i64, MangaMetadata> = match policy {
+            MetadataPolicy::Overwrite => HashMap::new(),
+            _ => resolve_manga_metadata(tx, &manga_ids, policy).await?,
+        };
+        for (manga_id, metadata) in reported {
+            resolved.entry(manga_id).or_insert(metadata);
+        }
+
         let mut manga_builder: QueryBuilder<Postgres> = QueryBuilder::new(
             r#"
             INSERT INTO mangas
-                (id, title, alt_title, url, public_url, rating, is_nsfw, cover_url, large_cover_url, state, author, source)
+                (id, title, alt_title, url, public_url, rating, content_rating, cover_url, large_cover_url, state, author, source)
         "#,
         );
 
         manga_builder.push_values(batch, |mut b, manga| {
-            let is_nsfw = match manga.nsfw {
-                Some(val) => {
-                    if val > 0 {
-                        true
-                    } else {
-                        match &manga.content_rating {
-                            Some(val) => val.to_lowercase() == "adult",
-                            None => false,
-                        }
-                    }
-                }
-                None => match &manga.content_rating {
-                    Some(val) => val.to_lowercase() == "adult",
-                    None => false,
-                },
-            };
-            let author = match &manga.author {
-                Some(val) => {
-                    let mut author = val.clone();
-                    author.truncate(120);
-                    Some(author)
-                }
-                None => None,
-            };
+            let metadata = &resolved[&manga.manga_id];
 
             b.push_bind(manga.manga_id)
-                .push_bind(&manga.title)
-                .push_bind(&manga.alt_title)
+                .push_bind(&metadata.title)
+                .push_bind(&metadata.alt_title)
                 .push_bind(&manga.url)
                 .push_bind(&manga.public_url)
                 .push_bind(manga.rating)
-                .push_bind(is_nsfw)
-                .push_bind(&manga.cover_url)
-                .push_bind(&manga.large_cover_url)
-                .push_bind(&manga.state)
-                .push_bind(author)
+                .push_bind(&metadata.content_rating)
+                .push_bind(&metadata.cover_url)
+                .push_bind(&metadata.large_cover_url)
+                .push_bind(&metadata.state)
+                .push_bind(&metadata.author)
                 .push_bind(&manga.source);
         });
 
@@ -239,9 +595,9 @@ pub async fn insert_mangas(tx: &mut PostgresTransaction, data: &[Arc<Manga>]) ->
                 title = EXCLUDED.title,
                 alt_title = EXCLUDED.alt_title,
                 url = EXCLUDED.url,
-                public_url = EXCLUDED.public_url,
+                public_url = COALESCE(NULLIF(EXCLUDED.public_url, ''), mangas.public_url),
                 rating = EXCLUDED.rating,
-                is_nsfw = EXCLUDED.is_nsfw,
+                content_rating = EXCLUDED.content_rating,
                 cover_url = EXCLUDED.cover_url,
                 large_cover_url = EXCLUDED.large_cover_url,
                 state = EXCLUDED.state,
@@ -255,7 +611,76 @@ pub async fn insert_mangas(tx: &mut PostgresTransaction, data: &[Arc<Manga>]) ->
             .execute(&mut **tx)
             .await
             .map_err(DatabaseError::DatabaseError)?;
+
+        let revisions: Vec<(i64, serde_json::Value)> = manga_ids
+            .iter()
+            .filter_map(|manga_id| {
+                diff_manga_metadata(current.get(manga_id), &resolved[manga_id])
+                    .map(|changes| (*manga_id, changes))
+            })
+            .collect();
+        insert_manga_revisions(tx, user_id, &revisions).await?;
     }
 
     Ok(())
 }
+
+/// The subset of `manga_ids` still present in `mangas`.
+#[tracing::instrument(name = "get existing manga ids", skip_all)]
+pub async fn get_existing_manga_ids(pool: &PgPool, manga_ids: &[i64]) -> Result<Vec<i64>, Error> {
+    let ids = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM mangas WHERE id = ANY($1)
+    "#,
+        manga_ids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(ids)
+}
+
+/// Deletes the given manga when no user references them anymore, along with their orphaned tags.
+#[tracing::instrument(name = "delete orphaned mangas", skip_all)]
+pub async fn delete_orphaned_mangas(
+    tx: &mut PostgresTransaction,
+    manga_ids: &[i64],
+) -> Result<(), Error> {
+    if manga_ids.is_empty() {
+        return Ok(());
+    }
+
+    let tag_ids: Vec<i64> = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT
+            tag_id
+        FROM
+            manga_tags
+        WHERE
+            manga_id = ANY($1);
+    "#,
+        manga_ids
+    )
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM mangas
+        WHERE
+            id = ANY($1)
+            AND NOT EXISTS (SELECT 1 FROM history WHERE history.manga_id = mangas.id)
+            AND NOT EXISTS (SELECT 1 FROM favourites WHERE favourites.manga_id = mangas.id)
+            AND NOT EXISTS (SELECT 1 FROM bookmarks WHERE bookmarks.manga_id = mangas.id)
+            AND NOT EXISTS (SELECT 1 FROM tracks WHERE tracks.manga_id = mangas.id);
+    "#,
+        manga_ids
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    delete_orphaned_tags(tx, &tag_ids).await
+}
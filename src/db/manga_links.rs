@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+
+use crate::{
+    db::{error::DatabaseError, manga::get_mangas_by_ids},
+    error::Error,
+    linking::LinkCandidate,
+    model::MangaLinkGroup,
+};
+
+use super::PostgresTransaction;
+
+/// Every manga in the user's history or favourites with the link group it is in.
+#[tracing::instrument(name = "get user link candidates", skip_all, fields(user_id))]
+pub async fn get_user_link_candidates(
+    pool: &PgPool,
+    user_id: i64,
+) -> Result<Vec<LinkCandidate>, Error> {
+    let candidates = sqlx::query_as!(
+        LinkCandidate,
+        r#"
+        SELECT
+            mangas.id AS manga_id, mangas.title, mangas.alt_title,
+            mangas.author, mangas.source,
+            manga_links.group_id AS "group_id?"
+        FROM
+            mangas
+        LEFT JOIN
+            manga_links ON manga_links.manga_id = mangas.id AND manga_links.user_id = $1
+        WHERE
+            EXISTS (
+                SELECT 1 FROM history
+                WHERE history.manga_id = mangas.id AND history.user_id = $1 AND history.deleted_at = 0
+            )
+            OR EXISTS (
+                SELECT 1 FROM favourites
+                WHERE favourites.manga_id = mangas.id AND favourites.user_id = $1 AND favourites.deleted_at = 0
+            )
+    "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(candidates)
+}
+
+/// The user's link groups with at least two manga left, newest first. Only the given group
+/// when `group_id` is set.
+#[tracing::instrument(name = "get user link groups", skip_all, fields(user_id))]
+pub async fn get_user_link_groups(
+    pool: &PgPool,
+    user_id: i64,
+    group_id: Option<i64>,
+) -> Result<Vec<MangaLinkGroup>, Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            manga_link_groups.id, manga_link_groups.created_at,
+            ARRAY_AGG(manga_links.manga_id ORDER BY manga_links.manga_id) AS "manga_ids!"
+        FROM
+            manga_link_groups
+        INNER JOIN
+            manga_links ON manga_links.group_id = manga_link_groups.id
+        WHERE
+            manga_link_groups.user_id = $1
+            AND ($2::bigint IS NULL OR manga_link_groups.id = $2)
+        GROUP BY manga_link_groups.id
+        HAVING COUNT(*) > 1
+        ORDER BY manga_link_groups.id DESC
+    "#,
+        user_id,
+        group_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let manga_ids: Vec<i64> = rows
+        .iter()
+        .flat_map(|row| row.manga_ids.iter().copied())
+        .collect();
+    let mangas: HashMap<i64, _> = get_mangas_by_ids(pool, &manga_ids)
+        .await?
+        .into_iter()
+        .map(|manga| (manga.manga_id, manga))
+        .collect();
+
+    let groups = rows
+        .into_iter()
+        .map(|row| MangaLinkGroup {
+            id: row.id,
+            manga: row
+                .manga_ids
+                .iter()
+                .filter_map(|manga_id| mangas.get(manga_id).cloned())
+                .collect(),
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(groups)
+}
+
+/// Links the manga into a new group, merging any group one of them was already in. Fails with
+/// `NotFound` when a manga is not in the user's library.
+#[tracing::instrument(name = "create user link group", skip_all, fields(user_id))]
+pub async fn create_user_link_group(
+    pool: &PgPool,
+    user_id: i64,
+    manga_ids: &[i64],
+) -> Result<i64, Error> {
+    let mut tx = pool.begin().await.map_err(DatabaseError::DatabaseError)?;
+
+    let in_library = sqlx::query_scalar!(
+        r#"
+        SELECT
+            COUNT(*) AS "count!"
+        FROM
+            mangas
+        WHERE
+            id = ANY($2)
+            AND (
+                EXISTS (
+                    SELECT 1 FROM history
+                    WHERE history.manga_id = mangas.id AND history.user_id = $1 AND history.deleted_at = 0
+                )
+                OR EXISTS (
+                    SELECT 1 FROM favourites
+                    WHERE favourites.manga_id = mangas.id AND favourites.user_id = $1 AND favourites.deleted_at = 0
+                )
+            )
+    "#,
+        user_id,
+        manga_ids
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+    if in_library != manga_ids.len() as i64 {
+        return Err(Error::Database(DatabaseError::NotFound));
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let group_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO manga_link_groups (user_id, created_at)
+        VALUES ($1, $2)
+        RETURNING id
+    "#,
+        user_id,
+        now
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let merged_group_ids = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT group_id
+        FROM
+            manga_links
+        WHERE
+            user_id = $1
+            AND manga_id = ANY($2)
+    "#,
+        user_id,
+        manga_ids
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    sqlx::query!(
+        r#"
+        UPDATE manga_links
+        SET
+            group_id = $3
+        WHERE
+            user_id = $1
+            AND group_id = ANY($2)
+    "#,
+        user_id,
+        &merged_group_ids,
+        group_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO manga_links (group_id, user_id, manga_id)
+        SELECT $3, $1, manga_id FROM UNNEST($2::bigint[]) AS manga_id
+        ON CONFLICT (user_id, manga_id) DO NOTHING
+    "#,
+        user_id,
+        manga_ids,
+        group_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM manga_link_groups
+        WHERE user_id = $1 AND id = ANY($2)
+    "#,
+        user_id,
+        &merged_group_ids
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    update_user_links_time(&mut tx, user_id, now).await?;
+
+    tx.commit().await.map_err(DatabaseError::DatabaseError)?;
+
+    Ok(group_id)
+}
+
+#[tracing::instrument(name = "delete user link group", skip_all, fields(user_id))]
+pub async fn delete_user_link_group(
+    pool: &PgPool,
+    user_id: i64,
+    group_id: i64,
+) -> Result<(), Error> {
+    let mut tx = pool.begin().await.map_err(DatabaseError::DatabaseError)?;
+
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM manga_link_groups
+        WHERE user_id = $1 AND id = $2
+    "#,
+        user_id,
+        group_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+    if result.rows_affected() == 0 {
+        return Err(Error::Database(DatabaseError::NotFound));
+    }
+
+    update_user_links_time(&mut tx, user_id, chrono::Utc::now().timestamp_millis()).await?;
+
+    tx.commit().await.map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Links change how stats count series, so their cache version follows this time too.
+async fn update_user_links_time(
+    tx: &mut PostgresTransaction,
+    user_id: i64,
+    time: i64,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET
+            links_updated_at = $1
+        WHERE
+            id = $2;
+    "#,
+        time,
+        user_id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
@@ -0,0 +1,443 @@
+use std::{collections::HashMap, hash::Hash};
+
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::{
+    config::MetadataPolicy,
+    db::error::DatabaseError,
+    error::Error,
+    model::{Manga, MangaRevision},
+};
+
+use super::PostgresTransaction;
+
+/// The manga fields a client parser reports, as stored in `mangas`.
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct MangaMetadata {
+    pub title: String,
+    pub alt_title: Option<String>,
+    pub cover_url: String,
+    pub large_cover_url: Option<String>,
+    pub state: Option<String>,
+    pub author: Option<String>,
+    pub content_rating: Option<String>,
+}
+
+impl From<&Manga> for MangaMetadata {
+    fn from(manga: &Manga) -> Self {
+        // The rating is kept as reported unless the `nsfw` flag contradicts it.
+        let is_adult = manga
+            .content_rating
+            .as_ref()
+            .is_some_and(|val| val.to_lowercase() == "adult");
+        let content_rating = match manga.nsfw {
+            Some(val) if val > 0 && !is_adult => Some("ADULT".to_string()),
+            // The columns are limited in characters, not bytes.
+            _ => manga
+                .content_rating
+                .as_ref()
+                .map(|val| val.chars().take(24).collect()),
+        };
+        let author = manga
+            .author
+            .as_ref()
+            .map(|val| val.chars().take(120).collect());
+
+        MangaMetadata {
+            title: manga.title.clone(),
+            alt_title: manga.alt_title.clone(),
+            cover_url: manga.cover_url.clone(),
+            large_cover_url: manga.large_cover_url.clone(),
+            state: manga.state.clone(),
+            author,
+            content_rating,
+        }
+    }
+}
+
+#[tracing::instrument(name = "get manga metadata", skip_all)]
+pub async fn get_manga_metadata(
+    tx: &mut PostgresTransaction,
+    manga_ids: &[i64],
+) -> Result<HashMap<i64, MangaMetadata>, Error> {
+    let metadata = sqlx::query!(
+        r#"
+        SELECT
+            id, title, alt_title, cover_url,
+            large_cover_url, state, author, content_rating
+        FROM
+            mangas
+        WHERE
+            id = ANY($1)
+    "#,
+        manga_ids
+    )
+    .map(|row| {
+        (
+            row.id,
+            MangaMetadata {
+                title: row.title,
+                alt_title: row.alt_title,
+                cover_url: row.cover_url,
+                large_cover_url: row.large_cover_url,
+                state: row.state,
+                author: row.author,
+                content_rating: row.content_rating,
+            },
+        )
+    })
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?
+    .into_iter()
+    .collect();
+
+    Ok(metadata)
+}
+
+/// Stores what the user's client reported for each manga. `changed_at` only moves when the
+/// reported values differ from the user's previous report.
+#[tracing::instrument(name = "insert manga reports", skip_all, fields(user_id))]
+pub async fn insert_manga_reports(
+    tx: &mut PostgresTransaction,
+    user_id: i64,
+    reports: &[(i64, MangaMetadata)],
+) -> Result<(), Error> {
+    if reports.is_empty() {
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut reports_query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+        r#"
+        INSERT INTO manga_reports
+            (manga_id, user_id, title, alt_title, cover_url, large_cover_url, state, author, content_rating, changed_at, reported_at)
+    "#,
+    );
+
+    reports_query_builder.push_values(reports, |mut b, (manga_id, metadata)| {
+        b.push_bind(manga_id)
+            .push_bind(user_id)
+            .push_bind(&metadata.title)
+            .push_bind(&metadata.alt_title)
+            .push_bind(&metadata.cover_url)
+            .push_bind(&metadata.large_cover_url)
+            .push_bind(&metadata.state)
+            .push_bind(&metadata.author)
+            .push_bind(&metadata.content_rating)
+            .push_bind(now)
+            .push_bind(now);
+    });
+    reports_query_builder.push(
+        r#"
+        ON CONFLICT (manga_id, user_id)
+        DO UPDATE SET
+            title = EXCLUDED.title,
+            alt_title = EXCLUDED.alt_title,
+            cover_url = EXCLUDED.cover_url,
+            large_cover_url = EXCLUDED.large_cover_url,
+            state = EXCLUDED.state,
+            author = EXCLUDED.author,
+            content_rating = EXCLUDED.content_rating,
+            changed_at = CASE
+                WHEN (
+                    manga_reports.title, manga_reports.alt_title, manga_reports.cover_url,
+                    manga_reports.large_cover_url, manga_reports.state, manga_reports.author,
+                    manga_reports.content_rating
+                ) IS DISTINCT FROM (
+                    EXCLUDED.title, EXCLUDED.alt_title, EXCLUDED.cover_url,
+                    EXCLUDED.large_cover_url, EXCLUDED.state, EXCLUDED.author,
+                    EXCLUDED.content_rating
+                )
+                THEN EXCLUDED.changed_at
+                ELSE manga_reports.changed_at
+            END,
+            reported_at = EXCLUDED.reported_at;
+    "#,
+    );
+
+    reports_query_builder
+        .build()
+        .execute(&mut **tx)
+        .await
+        .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Picks the metadata of each manga from all users' reports according to `policy`.
+/// Manga without any report are missing from the result.
+#[tracing::instrument(name = "resolve manga metadata", skip_all)]
+pub async fn resolve_manga_metadata(
+    tx: &mut PostgresTransaction,
+    manga_ids: &[i64],
+    policy: MetadataPolicy,
+) -> Result<HashMap<i64, MangaMetadata>, Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            manga_id, title, alt_title, cover_url,
+            large_cover_url, state, author, content_rating,
+            changed_at
+        FROM
+            manga_reports
+        WHERE
+            manga_id = ANY($1)
+        ORDER BY changed_at, reported_at
+    "#,
+        manga_ids
+    )
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    // Reports come oldest change first, the last one per manga is the most recent.
+    let mut reports: HashMap<i64, Vec<(MangaMetadata, i64)>> = HashMap::new();
+    for row in rows {
+        reports.entry(row.manga_id).or_default().push((
+            MangaMetadata {
+                title: row.title,
+                alt_title: row.alt_title,
+                cover_url: row.cover_url,
+                large_cover_url: row.large_cover_url,
+                state: row.state,
+                author: row.author,
+                content_rating: row.content_rating,
+            },
+            row.changed_at,
+        ));
+    }
+
+    let resolved = reports
+        .into_iter()
+        .filter_map(|(manga_id, reports)| {
+            let metadata = match policy {
+                MetadataPolicy::Overwrite | MetadataPolicy::MostRecent => reports.last()?.0.clone(),
+                MetadataPolicy::MostCommon => MangaMetadata {
+                    title: most_common(reports.iter().map(|(m, _)| &m.title))?.clone(),
+                    alt_title: most_common(reports.iter().map(|(m, _)| &m.alt_title))?.clone(),
+                    cover_url: most_common(reports.iter().map(|(m, _)| &m.cover_url))?.clone(),
+                    large_cover_url: most_common(reports.iter().map(|(m, _)| &m.large_cover_url))?
+                        .clone(),
+                    state: most_common(reports.iter().map(|(m, _)| &m.state))?.clone(),
+                    author: most_common(reports.iter().map(|(m, _)| &m.author))?.clone(),
+                    content_rating: most_common(reports.iter().map(|(m, _)| &m.content_rating))?
+                        .clone(),
+                },
+            };
+
+            Some((manga_id, metadata))
+        })
+        .collect();
+
+    Ok(resolved)
+}
+
+/// The most frequent value, ties go to the value seen last.
+fn most_common<T: Eq + Hash>(values: impl Iterator<Item = T>) -> Option<T> {
+    let mut counts: HashMap<T, (usize, usize)> = HashMap::new();
+    for (position, value) in values.enumerate() {
+        let entry = counts.entry(value).or_insert((0, position));
+        entry.0 += 1;
+        entry.1 = position;
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(value, _)| value)
+}
+
+/// Changed fields between two versions as `{"field": {"from": old, "to": new}}`, `None` when
+/// nothing changed.
+pub fn diff_manga_metadata(
+    from: Option<&MangaMetadata>,
+    to: &MangaMetadata,
+) -> Option<serde_json::Value> {
+    let from = from
+        .and_then(|from| serde_json::to_value(from).ok())
+        .unwrap_or_default();
+    let serde_json::Value::Object(to) = serde_json::to_value(to).ok()? else {
+        return None;
+    };
+
+    let mut changes = serde_json::Map::new();
+    for (field, value) in to {
+        let previous = from.get(&field).cloned().unwrap_or_default();
+        if previous != value {
+            changes.insert(field, serde_json::json!({ "from": previous, "to": value }));
+        }
+    }
+
+    if changes.is_empty() {
+        None
+    } else {
+        Some(serde_json::Value::Object(changes))
+    }
+}
+
+#[tracing::instrument(name = "insert manga revisions", skip_all)]
+pub async fn insert_manga_revisions(
+    tx: &mut PostgresTransaction,
+    user_id: Option<i64>,
+    revisions: &[(i64, serde_json::Value)],
+) -> Result<(), Error> {
+    if revisions.is_empty() {
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut revisions_query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+        r#"
+        INSERT INTO manga_revisions
+            (manga_id, user_id, changes, created_at)
+    "#,
+    );
+
+    revisions_query_builder.push_values(revisions, |mut b, (manga_id, changes)| {
+        b.push_bind(manga_id)
+            .push_bind(user_id)
+            .push_bind(changes)
+            .push_bind(now);
+    });
+
+    revisions_query_builder
+        .build()
+        .execute(&mut **tx)
+        .await
+        .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Revisions of the manga, newest first. `before_id` is the id of the last revision of the
+/// previous page.
+#[tracing::instrument(name = "get manga revisions", skip_all, fields(manga_id))]
+pub async fn get_manga_revisions(
+    pool: &PgPool,
+    manga_id: i64,
+    before_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<MangaRevision>, Error> {
+    let revisions = sqlx::query_as!(
+        MangaRevision,
+        r#"
+        SELECT
+            id, manga_id, user_id, changes, created_at
+        FROM
+            manga_revisions
+        WHERE
+            manga_id = $1
+            AND ($2::bigint IS NULL OR id < $2)
+        ORDER BY id DESC
+        LIMIT $3
+    "#,
+        manga_id,
+        before_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(revisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Manga;
+
+    use super::{MangaMetadata, diff_manga_metadata, most_common};
+
+    fn metadata(title: &str) -> MangaMetadata {
+        MangaMetadata {
+            title: title.to_string(),
+            alt_title: None,
+            cover_url: "https://localhost/cover.jpg".to_string(),
+            large_cover_url: None,
+            state: None,
+            author: None,
+            content_rating: None,
+        }
+    }
+
+    #[test]
+    fn most_common_should_prefer_last_value_on_tie() {
+        assert_eq!(most_common(["a", "b", "a"].into_iter()), Some("a"));
+        assert_eq!(most_common(["a", "b"].into_iter()), Some("b"));
+        assert_eq!(most_common(Vec::<&str>::new().into_iter()), None);
+    }
+
+    #[test]
+    fn diff_should_only_list_changed_fields() {
+        let changes = diff_manga_metadata(Some(&metadata("Old")), &metadata("New")).unwrap();
+        assert_eq!(
+            changes,
+            serde_json::json!({ "title": { "from": "Old", "to": "New" } })
+        );
+
+        assert!(diff_manga_metadata(Some(&metadata("Same")), &metadata("Same")).is_none());
+
+        let changes = diff_manga_metadata(None, &metadata("New")).unwrap();
+        assert_eq!(changes["title"]["from"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn from_manga_should_keep_content_rating_unless_flagged_nsfw() {
+        let rating = |nsfw: Option<u8>, content_rating: Option<&str>| {
+            let manga = Manga {
+                manga_id: 1,
+                title: "Title".to_string(),
+                alt_title: None,
+                url: "/manga/1".to_string(),
+                public_url: "https://localhost/manga/1".to_string(),
+                rating: 0.5,
+                nsfw,
+                content_rating: content_rating.map(str::to_string),
+                cover_url: "https://localhost/cover.jpg".to_string(),
+                large_cover_url: None,
+                state: None,
+                author: None,
+                source: "SOURCE".to_string(),
+                tags: Vec::new(),
+            };
+            MangaMetadata::from(&manga).content_rating
+        };
+
+        assert_eq!(rating(None, None), None);
+        assert_eq!(
+            rating(Some(0), Some("SUGGESTIVE")).as_deref(),
+            Some("SUGGESTIVE")
+        );
+        assert_eq!(rating(Some(1), Some("adult")).as_deref(), Some("adult"));
+        assert_eq!(rating(Some(1), Some("SAFE")).as_deref(), Some("ADULT"));
+        assert_eq!(rating(Some(1), None).as_deref(), Some("ADULT"));
+    }
+
+    #[test]
+    fn from_manga_should_truncate_by_characters() {
+        let manga = Manga {
+            manga_id: 1,
+            title: "Title".to_string(),
+            alt_title: None,
+            url: "/manga/1".to_string(),
+            public_url: "https://localhost/manga/1".to_string(),
+            rating: 0.5,
+            nsfw: None,
+            content_rating: Some("é".repeat(30)),
+            cover_url: "https://localhost/cover.jpg".to_string(),
+            large_cover_url: None,
+            state: None,
+            author: Some("尾田栄一郎".repeat(30)),
+            source: "SOURCE".to_string(),
+            tags: Vec::new(),
+        };
+
+        let metadata = MangaMetadata::from(&manga);
+        assert_eq!(metadata.content_rating, Some("é".repeat(24)));
+        assert_eq!(
+            metadata.author.map(|author| author.chars().count()),
+            Some(120)
+        );
+    }
+}
@@ -2,10 +2,21 @@ use sqlx::{Postgres, Transaction};
 
 pub mod error;
 pub mod manga;
+pub mod manga_links;
+pub mod manga_revisions;
 pub mod manga_tags;
+pub mod public_lists;
+pub mod recommendations;
+pub mod stats;
 pub mod tags;
 pub mod user;
+pub mod user_bookmarks;
+pub mod user_chapters;
 pub mod user_favourites;
 pub mod user_history;
+pub mod user_settings;
+pub mod user_sources;
+pub mod user_timeline;
+pub mod user_tracks;
 
 pub type PostgresTransaction = Transaction<'static, Postgres>;
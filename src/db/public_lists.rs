@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+
+use crate::{
+    db::error::DatabaseError,
+    error::Error,
+    model::{ContentRating, PublicList, SharedList, SharedListItem},
+};
+
+use super::manga::get_mangas_by_ids;
+
+#[tracing::instrument(name = "get user public lists", skip_all, fields(user_id))]
+pub async fn get_user_public_lists(pool: &PgPool, user_id: i64) -> Result<Vec<PublicList>, Error> {
+    let lists = sqlx::query_as!(
+        PublicList,
+        r#"
+        SELECT
+            id AS category_id, title, public_slug AS "slug!", public_hide_nsfw AS hide_nsfw
+        FROM
+            categories
+        WHERE
+            user_id = $1
+            AND public_slug IS NOT NULL
+        ORDER BY sort_key, id
+    "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(lists)
+}
+
+/// Publishes the category under `slug`, keeping the existing slug when it is already public.
+#[tracing::instrument(name = "publish category", skip_all, fields(user_id, category_id))]
+pub async fn publish_category(
+    pool: &PgPool,
+    user_id: i64,
+    category_id: i64,
+    slug: &str,
+    hide_nsfw: Option<bool>,
+) -> Result<PublicList, Error> {
+    sqlx::query_as!(
+        PublicList,
+        r#"
+        UPDATE categories
+        SET
+            public_slug = COALESCE(public_slug, $3),
+            public_hide_nsfw = COALESCE($4, public_hide_nsfw)
+        WHERE
+            id = $1
+            AND user_id = $2
+            AND deleted_at = 0
+        RETURNING
+            id AS category_id, title, public_slug AS "slug!", public_hide_nsfw AS hide_nsfw
+    "#,
+        category_id,
+        user_id,
+        slug,
+        hide_nsfw
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?
+    .ok_or(Error::Database(DatabaseError::NotFound))
+}
+
+#[tracing::instrument(name = "unpublish category", skip_all, fields(user_id, category_id))]
+pub async fn unpublish_category(
+    pool: &PgPool,
+    user_id: i64,
+    category_id: i64,
+) -> Result<(), Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE categories
+        SET
+            public_slug = NULL,
+            public_hide_nsfw = false
+        WHERE
+            id = $1
+            AND user_id = $2
+            AND public_slug IS NOT NULL
+    "#,
+        category_id,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::Database(DatabaseError::NotFound));
+    }
+
+    Ok(())
+}
+
+/// The published category behind `slug` with its favourites, newest first. Manga rated above
+/// `max_content_rating` are left out, as are adult manga when the owner hid NSFW.
+#[tracing::instrument(name = "get shared list", skip_all)]
+pub async fn get_shared_list(
+    pool: &PgPool,
+    slug: &str,
+    max_content_rating: ContentRating,
+) -> Result<SharedList, Error> {
+    let category = sqlx::query!(
+        r#"
+        SELECT
+            categories.id, categories.user_id, categories.title,
+            categories.public_hide_nsfw, users.nickname
+        FROM
+            categories
+        INNER JOIN
+            users ON users.id = categories.user_id
+        WHERE
+            categories.public_slug = $1
+            AND categories.deleted_at = 0
+            AND users.is_disabled = false
+    "#,
+        slug
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?
+    .ok_or(Error::Database(DatabaseError::NotFound))?;
+
+    let favourites = sqlx::query!(
+        r#"
+        SELECT
+            favourites.manga_id, favourites.created_at
+        FROM
+            favourites
+        INNER JOIN
+            mangas ON mangas.id = favourites.manga_id
+        WHERE
+            favourites.category_id = $1
+            AND favourites.user_id = $2
+            AND favourites.deleted_at = 0
+            AND mangas.content_level <= $3
+        ORDER BY favourites.created_at DESC, favourites.manga_id
+    "#,
+        category.id,
+        category.user_id,
+        if category.public_hide_nsfw {
+            max_content_rating.min(ContentRating::Suggestive)
+        } else {
+            max_content_rating
+        }
+        .level()
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let manga_ids: Vec<i64> = favourites.iter().map(|f| f.manga_id).collect();
+    let mangas = get_mangas_by_ids(pool, &manga_ids).await?;
+
+    let items = favourites
+        .into_iter()
+        .filter_map(|favourite| {
+            mangas
+                .iter()
+                .find(|m| m.manga_id == favourite.manga_id)
+                .map(|manga| SharedListItem {
+                    manga: Arc::clone(manga),
+                    created_at: favourite.created_at,
+                })
+        })
+        .collect();
+
+    Ok(SharedList {
+        title: category.title,
+        nickname: category.nickname,
+        items,
+    })
+}
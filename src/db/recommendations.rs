@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+
+use crate::{
+    db::{error::DatabaseError, manga::get_mangas_by_ids},
+    error::Error,
+    model::{ContentRating, Recommendation},
+};
+
+/// Manga outside the user's library, ranked by how well their tags match the tags of the
+/// library (matched by title across sources) plus how many similar users favourited them.
+/// Users are similar by the number of favourites they share with the library. Only users
+/// sharing their reading signals count, and only manga in such a user's library are suggested.
+#[tracing::instrument(name = "get user recommendations", skip_all, fields(user_id))]
+pub async fn get_user_recommendations(
+    pool: &PgPool,
+    user_id: i64,
+    max_content_rating: ContentRating,
+    limit: i64,
+) -> Result<Vec<Recommendation>, Error> {
+    let rows = sqlx::query!(
+        r#"
+        WITH library AS (
+            SELECT manga_id FROM history WHERE user_id = $1 AND deleted_at = 0
+            UNION
+            SELECT manga_id FROM favourites WHERE user_id = $1 AND deleted_at = 0
+        ),
+        profile AS (
+            SELECT
+                LOWER(tags.title) AS tag,
+                COUNT(DISTINCT library.manga_id)::float8 AS weight
+            FROM
+                library
+            INNER JOIN
+                manga_tags ON manga_tags.manga_id = library.manga_id
+            INNER JOIN
+                tags ON tags.id = manga_tags.tag_id
+            GROUP BY 1
+        ),
+        neighbours AS (
+            SELECT
+                favourites.user_id,
+                COUNT(DISTINCT favourites.manga_id)::float8 AS similarity
+            FROM
+                favourites
+            INNER JOIN
+                users ON users.id = favourites.user_id
+            WHERE
+                favourites.manga_id IN (SELECT manga_id FROM library)
+                AND favourites.user_id <> $1
+                AND favourites.deleted_at = 0
+                AND users.share_reading_signals
+            GROUP BY favourites.user_id
+        ),
+        tag_scores AS (
+            SELECT
+                manga_tag_titles.manga_id,
+                SUM(profile.weight) / (SELECT SUM(weight) FROM profile) AS score
+            FROM (
+                SELECT DISTINCT manga_tags.manga_id, LOWER(tags.title) AS tag
+                FROM manga_tags
+                INNER JOIN tags ON tags.id = manga_tags.tag_id
+            ) AS manga_tag_titles
+            INNER JOIN
+                profile ON profile.tag = manga_tag_titles.tag
+            GROUP BY manga_tag_titles.manga_id
+        ),
+        collaborative_scores AS (
+            SELECT
+                favourites.manga_id,
+                SUM(neighbours.similarity) / (SELECT SUM(similarity) FROM neighbours) AS score
+            FROM (
+                SELECT DISTINCT user_id, manga_id FROM favourites WHERE deleted_at = 0
+            ) AS favourites
+            INNER JOIN
+                neighbours ON neighbours.user_id = favourites.user_id
+            GROUP BY favourites.manga_id
+        )
+        SELECT
+            mangas.id,
+            COALESCE(tag_scores.score, 0) AS "tag_score!",
+            COALESCE(collaborative_scores.score, 0) AS "collaborative_score!"
+        FROM
+            mangas
+        LEFT JOIN
+            tag_scores ON tag_scores.manga_id = mangas.id
+        LEFT JOIN
+            collaborative_scores ON collaborative_scores.manga_id = mangas.id
+        WHERE
+            (tag_scores.manga_id IS NOT NULL OR collaborative_scores.manga_id IS NOT NULL)
+            AND mangas.id NOT IN (SELECT manga_id FROM library)
+            AND mangas.content_level <= $2
+            AND (
+                EXISTS (
+                    SELECT 1 FROM favourites
+                    INNER JOIN users ON users.id = favourites.user_id
+                    WHERE favourites.manga_id = mangas.id AND favourites.deleted_at = 0
+                        AND users.share_reading_signals
+                )
+                OR EXISTS (
+                    SELECT 1 FROM history
+                    INNER JOIN users ON users.id = history.user_id
+                    WHERE history.manga_id = mangas.id AND history.deleted_at = 0
+                        AND users.share_reading_signals
+                )
+            )
+        ORDER BY COALESCE(tag_scores.score, 0) + COALESCE(collaborative_scores.score, 0) DESC, mangas.id
+        LIMIT $3
+    "#,
+        user_id,
+        max_content_rating.level(),
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let manga_ids: Vec<i64> = rows.iter().map(|row| row.id).collect();
+    let mangas: HashMap<i64, _> = get_mangas_by_ids(pool, &manga_ids)
+        .await?
+        .into_iter()
+        .map(|manga| (manga.manga_id, manga))
+        .collect();
+
+    let recommendations = rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(Recommendation {
+                manga: mangas.get(&row.id)?.clone(),
+                tag_score: row.tag_score,
+                collaborative_score: row.collaborative_score,
+                score: row.tag_score + row.collaborative_score,
+            })
+        })
+        .collect();
+
+    Ok(recommendations)
+}
@@ -0,0 +1,262 @@
+use sqlx::PgPool;
+
+use crate::{
+    db::error::DatabaseError,
+    error::Error,
+    model::{ReadingSummary, SourceStat, Tag, TagStat, WeeklyChapters},
+};
+
+/// Changes whenever the history or chapter reads of the user are synced, or manga are linked.
+#[tracing::instrument(name = "get user stats version", skip_all, fields(user_id))]
+pub async fn get_user_stats_version(pool: &PgPool, user_id: i64) -> Result<i64, Error> {
+    let version = sqlx::query_scalar!(
+        r#"
+        SELECT
+            GREATEST(
+                COALESCE(history_sync_timestamp, 0),
+                COALESCE(chapters_sync_timestamp, 0),
+                COALESCE(links_updated_at, 0)
+            ) AS "version!"
+        FROM
+            users
+        WHERE
+            id = $1
+    "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(version)
+}
+
+/// Series started within the range (by `created_at`), how many of them are finished, and the
+/// chapters read within the range. Linked manga count as a single series.
+///
+/// A chapter counts as read from the first time it shows up in the chapter reads, the history
+/// timeline or the current history position, so clients that never sync chapter reads still
+/// get numbers.
+#[tracing::instrument(name = "get user reading summary", skip_all, fields(user_id))]
+pub async fn get_user_reading_summary(
+    pool: &PgPool,
+    user_id: i64,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<ReadingSummary, Error> {
+    let history = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) AS "started!",
+            COUNT(*) FILTER (WHERE percent >= 1) AS "finished!"
+        FROM (
+            SELECT
+                MIN(history.created_at) AS created_at,
+                MAX(history.percent) AS percent
+            FROM
+                history
+            LEFT JOIN
+                manga_links ON manga_links.user_id = history.user_id
+                    AND manga_links.manga_id = history.manga_id
+            WHERE
+                history.user_id = $1
+                AND history.deleted_at = 0
+            GROUP BY COALESCE(manga_links.group_id, -history.manga_id)
+        ) AS series
+        WHERE
+            ($2::bigint IS NULL OR created_at >= $2)
+            AND ($3::bigint IS NULL OR created_at < $3)
+    "#,
+        user_id,
+        from,
+        to
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let chapters_read = sqlx::query_scalar!(
+        r#"
+        WITH reads AS (
+            SELECT manga_id, chapter_id, read_at FROM chapters_read
+            WHERE user_id = $1 AND deleted_at < read_at
+            UNION ALL
+            SELECT manga_id, chapter_id, recorded_at FROM history_timeline
+            WHERE user_id = $1
+            UNION ALL
+            SELECT manga_id, chapter_id, updated_at FROM history
+            WHERE user_id = $1 AND deleted_at = 0
+        ),
+        chapters AS (
+            SELECT manga_id, chapter_id, MIN(read_at) AS read_at
+            FROM reads
+            GROUP BY manga_id, chapter_id
+        )
+        SELECT
+            COUNT(*) AS "chapters_read!"
+        FROM
+            chapters
+        WHERE
+            ($2::bigint IS NULL OR read_at >= $2)
+            AND ($3::bigint IS NULL OR read_at < $3)
+    "#,
+        user_id,
+        from,
+        to
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(ReadingSummary {
+        started: history.started,
+        finished: history.finished,
+        completion_rate: if history.started == 0 {
+            0.0
+        } else {
+            history.finished as f64 / history.started as f64
+        },
+        chapters_read,
+    })
+}
+
+/// Chapters read per week, counted like in [`get_user_reading_summary`].
+#[tracing::instrument(name = "get user weekly chapters", skip_all, fields(user_id))]
+pub async fn get_user_weekly_chapters(
+    pool: &PgPool,
+    user_id: i64,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<Vec<WeeklyChapters>, Error> {
+    let weeks = sqlx::query_as!(
+        WeeklyChapters,
+        r#"
+        WITH reads AS (
+            SELECT manga_id, chapter_id, read_at FROM chapters_read
+            WHERE user_id = $1 AND deleted_at < read_at
+            UNION ALL
+            SELECT manga_id, chapter_id, recorded_at FROM history_timeline
+            WHERE user_id = $1
+            UNION ALL
+            SELECT manga_id, chapter_id, updated_at FROM history
+            WHERE user_id = $1 AND deleted_at = 0
+        ),
+        chapters AS (
+            SELECT manga_id, chapter_id, MIN(read_at) AS read_at
+            FROM reads
+            GROUP BY manga_id, chapter_id
+        )
+        SELECT
+            (EXTRACT(EPOCH FROM date_trunc('week', to_timestamp(read_at / 1000.0) AT TIME ZONE 'UTC')) * 1000)::bigint AS "week!",
+            COUNT(*) AS "chapters!"
+        FROM
+            chapters
+        WHERE
+            ($2::bigint IS NULL OR read_at >= $2)
+            AND ($3::bigint IS NULL OR read_at < $3)
+        GROUP BY 1
+        ORDER BY 1
+    "#,
+        user_id,
+        from,
+        to
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(weeks)
+}
+
+/// Most frequent tags among the series read within the range (by `updated_at`).
+#[tracing::instrument(name = "get user top tags", skip_all, fields(user_id))]
+pub async fn get_user_top_tags(
+    pool: &PgPool,
+    user_id: i64,
+    from: Option<i64>,
+    to: Option<i64>,
+    limit: i64,
+) -> Result<Vec<TagStat>, Error> {
+    let tags = sqlx::query!(
+        r#"
+        SELECT
+            tags.id, tags.title, tags."key", tags.source,
+            COUNT(DISTINCT COALESCE(manga_links.group_id, -history.manga_id)) AS "count!"
+        FROM
+            history
+        LEFT JOIN
+            manga_links ON manga_links.user_id = history.user_id
+                AND manga_links.manga_id = history.manga_id
+        INNER JOIN
+            manga_tags ON manga_tags.manga_id = history.manga_id
+        INNER JOIN
+            tags ON tags.id = manga_tags.tag_id
+        WHERE
+            history.user_id = $1
+            AND history.deleted_at = 0
+            AND ($2::bigint IS NULL OR history.updated_at >= $2)
+            AND ($3::bigint IS NULL OR history.updated_at < $3)
+        GROUP BY tags.id
+        ORDER BY 5 DESC, tags.id
+        LIMIT $4
+    "#,
+        user_id,
+        from,
+        to,
+        limit
+    )
+    .map(|row| TagStat {
+        tag: Tag {
+            tag_id: row.id,
+            title: row.title,
+            key: row.key,
+            source: row.source,
+        },
+        count: row.count,
+    })
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(tags)
+}
+
+/// Most read sources among the mangas read within the range (by `updated_at`).
+#[tracing::instrument(name = "get user top sources", skip_all, fields(user_id))]
+pub async fn get_user_top_sources(
+    pool: &PgPool,
+    user_id: i64,
+    from: Option<i64>,
+    to: Option<i64>,
+    limit: i64,
+) -> Result<Vec<SourceStat>, Error> {
+    let sources = sqlx::query_as!(
+        SourceStat,
+        r#"
+        SELECT
+            mangas.source,
+            COUNT(*) AS "count!"
+        FROM
+            history
+        INNER JOIN
+            mangas ON mangas.id = history.manga_id
+        WHERE
+            history.user_id = $1
+            AND history.deleted_at = 0
+            AND ($2::bigint IS NULL OR history.updated_at >= $2)
+            AND ($3::bigint IS NULL OR history.updated_at < $3)
+        GROUP BY mangas.source
+        ORDER BY 2 DESC, mangas.source
+        LIMIT $4
+    "#,
+        user_id,
+        from,
+        to,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(sources)
+}
@@ -1,10 +1,81 @@
 use std::sync::Arc;
 
-use sqlx::{Postgres, QueryBuilder};
+use sqlx::{PgPool, Postgres, QueryBuilder};
 
-use crate::{error::Error, model::Tag};
+use crate::{
+    error::Error,
+    model::{Tag, TagStat},
+};
 
-use super::{PostgresTransaction, error::DatabaseError};
+use super::{PostgresTransaction, error::DatabaseError, manga::MangaScope};
+
+/// Tags with the number of manga using them, most used first. `after` is the
+/// `(count, tag_id)` of the last tag of the previous page.
+#[tracing::instrument(name = "get tags with usage", skip_all)]
+pub async fn get_tags_with_usage(
+    pool: &PgPool,
+    scope: MangaScope,
+    source: Option<&str>,
+    after: Option<(i64, i64)>,
+    limit: i64,
+) -> Result<Vec<TagStat>, Error> {
+    let (after_count, after_id) = after.unzip();
+
+    let tags = sqlx::query!(
+        r#"
+        SELECT
+            tags.id, tags.title, tags."key", tags.source,
+            COUNT(*) AS "count!"
+        FROM
+            tags
+        INNER JOIN
+            manga_tags ON manga_tags.tag_id = tags.id
+        INNER JOIN
+            mangas ON mangas.id = manga_tags.manga_id
+        WHERE
+            ($1::text IS NULL OR tags.source = $1)
+            AND mangas.content_level <= $6
+            AND (
+                $2::bigint IS NULL
+                OR EXISTS (
+                    SELECT 1 FROM history
+                    WHERE history.manga_id = manga_tags.manga_id AND history.user_id = $2 AND history.deleted_at = 0
+                )
+                OR EXISTS (
+                    SELECT 1 FROM favourites
+                    WHERE favourites.manga_id = manga_tags.manga_id AND favourites.user_id = $2 AND favourites.deleted_at = 0
+                )
+            )
+        GROUP BY tags.id
+        HAVING
+            $3::bigint IS NULL
+            OR COUNT(*) < $3
+            OR (COUNT(*) = $3 AND tags.id > $4)
+        ORDER BY 5 DESC, tags.id
+        LIMIT $5
+    "#,
+        source,
+        scope.user_id,
+        after_count,
+        after_id,
+        limit,
+        scope.max_content_rating.level()
+    )
+    .map(|row| TagStat {
+        tag: Tag {
+            tag_id: row.id,
+            title: row.title,
+            key: row.key,
+            source: row.source,
+        },
+        count: row.count,
+    })
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(tags)
+}
 
 pub async fn insert_tags(tx: &mut PostgresTransaction, data: &[Arc<Tag>]) -> Result<(), Error> {
     for batch in data.chunks(300) {
@@ -40,3 +111,27 @@ pub async fn insert_tags(tx: &mut PostgresTransaction, data: &[Arc<Tag>]) -> Res
 
     Ok(())
 }
+
+pub async fn delete_orphaned_tags(
+    tx: &mut PostgresTransaction,
+    tag_ids: &[i64],
+) -> Result<(), Error> {
+    if tag_ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        DELETE FROM tags
+        WHERE
+            id = ANY($1)
+            AND NOT EXISTS (SELECT 1 FROM manga_tags WHERE manga_tags.tag_id = tags.id);
+    "#,
+        tag_ids
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
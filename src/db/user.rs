@@ -5,11 +5,11 @@ use sqlx::PgPool;
 use crate::{
     auth::{compute_password_hash, error::AuthError},
     error::Error,
-    model::User,
+    model::{ContentRating, User, UserSummary},
     telemetry::spawn_blocking_with_tracing,
 };
 
-use super::{PostgresTransaction, error::DatabaseError};
+use super::{PostgresTransaction, error::DatabaseError, manga::delete_orphaned_mangas};
 
 #[tracing::instrument(
     name = "get or create user",
@@ -25,7 +25,9 @@ pub async fn get_or_create_user(
     let user_option = sqlx::query!(
         r#"
         SELECT 
-            id, email, password, nickname
+            id, email, password, nickname, is_admin, is_disabled,
+            share_reading_signals,
+            max_content_rating AS "max_content_rating: ContentRating"
         FROM
             users
         WHERE 
@@ -45,6 +47,10 @@ pub async fn get_or_create_user(
                 id: row.id,
                 email: row.email,
                 nickname: row.nickname,
+                is_admin: row.is_admin,
+                is_disabled: row.is_disabled,
+                share_reading_signals: row.share_reading_signals,
+                max_content_rating: row.max_content_rating,
             },
             row.password,
         ));
@@ -68,26 +74,46 @@ pub async fn create_user(
         .context("compute password hash")
         .map_err(Error::Other)??;
 
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| Error::Database(DatabaseError::DatabaseError(e)))?;
+
+    // The first registered user owns the instance and becomes its admin. The lock keeps two
+    // concurrent first registrations from both seeing an empty table.
+    sqlx::query!("LOCK TABLE users IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| Error::Database(DatabaseError::DatabaseError(e)))?;
+
     let user_id = sqlx::query!(
         r#"
         INSERT INTO USERS 
-            (email, password)
+            (email, password, is_admin)
         VALUES 
-            ($1, $2)
-        RETURNING id;
+            ($1, $2, NOT EXISTS (SELECT 1 FROM users))
+        RETURNING id, is_admin, share_reading_signals;
     "#,
         email,
         password_hashed
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| Error::Database(DatabaseError::DatabaseError(e)))?;
 
+    tx.commit()
+        .await
+        .map_err(|e| Error::Database(DatabaseError::DatabaseError(e)))?;
+
     Ok((
         User {
             id: user_id.id,
             email,
             nickname: None,
+            is_admin: user_id.is_admin,
+            is_disabled: false,
+            share_reading_signals: user_id.share_reading_signals,
+            max_content_rating: None,
         },
         password_hashed,
     ))
@@ -99,7 +125,8 @@ pub async fn get_user_by_id_optional(pool: &PgPool, user_id: i64) -> Result<Opti
         User,
         r#"
         SELECT 
-            id, email, nickname 
+            id, email, nickname, is_admin, is_disabled, share_reading_signals,
+            max_content_rating AS "max_content_rating: ContentRating"
         FROM 
             users
         WHERE 
@@ -157,3 +184,350 @@ pub async fn update_user_favourite_sync_time(
 
     Ok(())
 }
+
+pub async fn update_user_bookmark_sync_time(
+    tx: &mut PostgresTransaction,
+    user_id: i64,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET
+            bookmarks_sync_timestamp = $1
+        WHERE 
+            id = $2;
+    "#,
+        chrono::Utc::now().timestamp_millis(),
+        user_id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn update_user_chapter_sync_time(
+    tx: &mut PostgresTransaction,
+    user_id: i64,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET
+            chapters_sync_timestamp = $1
+        WHERE 
+            id = $2;
+    "#,
+        chrono::Utc::now().timestamp_millis(),
+        user_id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn update_user_track_sync_time(
+    tx: &mut PostgresTransaction,
+    user_id: i64,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET
+            tracks_sync_timestamp = $1
+        WHERE 
+            id = $2;
+    "#,
+        chrono::Utc::now().timestamp_millis(),
+        user_id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn update_user_source_sync_time(
+    tx: &mut PostgresTransaction,
+    user_id: i64,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET
+            sources_sync_timestamp = $1
+        WHERE 
+            id = $2;
+    "#,
+        chrono::Utc::now().timestamp_millis(),
+        user_id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn update_user_setting_sync_time(
+    tx: &mut PostgresTransaction,
+    user_id: i64,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET
+            settings_sync_timestamp = $1
+        WHERE 
+            id = $2;
+    "#,
+        chrono::Utc::now().timestamp_millis(),
+        user_id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "get users summary", skip_all, fields(search))]
+pub async fn get_users_summary(
+    pool: &PgPool,
+    search: Option<String>,
+    limit: i64,
+    skip: i64,
+) -> Result<Vec<UserSummary>, Error> {
+    let search = search.map(|value| {
+        value
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    });
+
+    sqlx::query_as!(
+        UserSummary,
+        r#"
+        SELECT
+            users.id, users.email, users.nickname,
+            users.is_admin, users.is_disabled,
+            (
+                SELECT count(DISTINCT favourites.manga_id)
+                FROM favourites
+                WHERE favourites.user_id = users.id AND favourites.deleted_at = 0
+            ) AS "favourites_count!",
+            (
+                SELECT count(*)
+                FROM history
+                WHERE history.user_id = users.id AND history.deleted_at = 0
+            ) AS "history_count!",
+            users.favourites_sync_timestamp, users.history_sync_timestamp
+        FROM
+            users
+        WHERE
+            $1::text IS NULL
+            OR users.email ILIKE '%' || $1 || '%'
+            OR users.nickname ILIKE '%' || $1 || '%'
+        ORDER BY users.id
+        LIMIT $2
+        OFFSET $3
+    "#,
+        search,
+        limit,
+        skip
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Error::Database(DatabaseError::DatabaseError(e)))
+}
+
+#[tracing::instrument(name = "get user summary by id", skip_all, fields(user_id))]
+pub async fn get_user_summary_by_id(pool: &PgPool, user_id: i64) -> Result<UserSummary, Error> {
+    sqlx::query_as!(
+        UserSummary,
+        r#"
+        SELECT
+            users.id, users.email, users.nickname,
+            users.is_admin, users.is_disabled,
+            (
+                SELECT count(DISTINCT favourites.manga_id)
+                FROM favourites
+                WHERE favourites.user_id = users.id AND favourites.deleted_at = 0
+            ) AS "favourites_count!",
+            (
+                SELECT count(*)
+                FROM history
+                WHERE history.user_id = users.id AND history.deleted_at = 0
+            ) AS "history_count!",
+            users.favourites_sync_timestamp, users.history_sync_timestamp
+        FROM
+            users
+        WHERE
+            users.id = $1;
+    "#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| Error::Database(DatabaseError::DatabaseError(e)))?
+    .ok_or(Error::Database(DatabaseError::NotFound))
+}
+
+#[tracing::instrument(name = "update user role", skip_all, fields(user_id))]
+pub async fn update_user_role(
+    pool: &PgPool,
+    user_id: i64,
+    is_admin: Option<bool>,
+    is_disabled: Option<bool>,
+) -> Result<(), Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET
+            is_admin = COALESCE($1, is_admin),
+            is_disabled = COALESCE($2, is_disabled)
+        WHERE
+            id = $3;
+    "#,
+        is_admin,
+        is_disabled,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::Database(DatabaseError::NotFound));
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "is email taken", skip_all, fields(user_id))]
+pub async fn is_email_taken(pool: &PgPool, email: &str, user_id: i64) -> Result<bool, Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM users WHERE email = $1 AND id <> $2
+        ) AS "exists!";
+    "#,
+        email,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| Error::Database(DatabaseError::DatabaseError(e)))
+}
+
+/// Returns `Ok(None)` when the new email collides with `users_email_index`. `nickname` and
+/// `max_content_rating` are left as is when `None` and cleared when `Some(None)`.
+#[tracing::instrument(name = "update user profile", skip_all, fields(user_id))]
+pub async fn update_user_profile(
+    pool: &PgPool,
+    user_id: i64,
+    email: Option<String>,
+    nickname: Option<Option<String>>,
+    share_reading_signals: Option<bool>,
+    max_content_rating: Option<Option<ContentRating>>,
+) -> Result<Option<User>, Error> {
+    let result = sqlx::query_as!(
+        User,
+        r#"
+        UPDATE users
+        SET
+            email = COALESCE($1, email),
+            nickname = CASE WHEN $2 THEN $3 ELSE nickname END,
+            share_reading_signals = COALESCE($4, share_reading_signals),
+            max_content_rating = CASE WHEN $5 THEN $6 ELSE max_content_rating END
+        WHERE
+            id = $7
+        RETURNING
+            id, email, nickname, is_admin, is_disabled, share_reading_signals,
+            max_content_rating AS "max_content_rating: ContentRating";
+    "#,
+        email,
+        nickname.is_some(),
+        nickname.flatten(),
+        share_reading_signals,
+        max_content_rating.is_some(),
+        max_content_rating.flatten() as Option<ContentRating>,
+        user_id
+    )
+    .fetch_one(pool)
+    .await;
+
+    match result {
+        Ok(user) => Ok(Some(user)),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => Ok(None),
+        Err(e) => Err(Error::Database(DatabaseError::DatabaseError(e))),
+    }
+}
+
+#[tracing::instrument(name = "get user password by id", skip_all, fields(user_id))]
+pub async fn get_user_password_by_id(pool: &PgPool, user_id: i64) -> Result<String, Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT
+            password
+        FROM
+            users
+        WHERE
+            id = $1;
+    "#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| Error::Database(DatabaseError::DatabaseError(e)))?
+    .ok_or(Error::Database(DatabaseError::NotFound))
+}
+
+/// Deletes the user with all of their categories, favourites, history, bookmarks and tracks,
+/// then purges manga and tags no other user references anymore.
+#[tracing::instrument(name = "delete user", skip_all, fields(user_id))]
+pub async fn delete_user(pool: &PgPool, user_id: i64) -> Result<(), Error> {
+    let mut tx = pool.begin().await.map_err(DatabaseError::DatabaseError)?;
+
+    let manga_ids: Vec<i64> = sqlx::query_scalar!(
+        r#"
+        SELECT manga_id AS "manga_id!" FROM history WHERE user_id = $1
+        UNION
+        SELECT manga_id AS "manga_id!" FROM favourites WHERE user_id = $1
+        UNION
+        SELECT manga_id AS "manga_id!" FROM bookmarks WHERE user_id = $1
+        UNION
+        SELECT manga_id AS "manga_id!" FROM tracks WHERE user_id = $1;
+    "#,
+        user_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM users
+        WHERE
+            id = $1;
+    "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::Database(DatabaseError::NotFound));
+    }
+
+    delete_orphaned_mangas(&mut tx, &manga_ids).await?;
+
+    tx.commit().await.map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use futures::TryStreamExt;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::{
+    config::MetadataPolicy,
+    db::error::DatabaseError,
+    error::Error,
+    model::{Bookmark, Manga, MangaTagEntity, Tag, UserBookmark},
+};
+
+use super::{
+    manga::{get_mangas_by_ids, insert_mangas},
+    manga_tags::insert_manga_tags,
+    tags::insert_tags,
+    user::update_user_bookmark_sync_time,
+};
+
+#[tracing::instrument(name = "get user_bookmark", skip_all)]
+pub async fn get_user_bookmarks(pool: &PgPool, user_id: i64) -> Result<UserBookmark, Error> {
+    let manga_ids = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT
+            manga_id
+        FROM
+            bookmarks
+        WHERE
+            user_id = $1
+    "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    if manga_ids.is_empty() {
+        return Ok(UserBookmark {
+            bookmarks: vec![],
+            timestamp: chrono::Utc::now().timestamp(),
+        });
+    }
+
+    let mangas = get_mangas_by_ids(pool, &manga_ids).await?;
+
+    let mut bookmarks_stream = sqlx::query!(
+        r#"
+        SELECT
+            manga_id, page_id, chapter_id,
+            page, scroll, image_url,
+            created_at, percent, deleted_at
+        FROM
+            bookmarks
+        WHERE
+            user_id = $1
+    "#,
+        user_id
+    )
+    .fetch(pool);
+
+    let mut bookmarks = Vec::new();
+    while let Some(row) = bookmarks_stream
+        .try_next()
+        .await
+        .map_err(DatabaseError::DatabaseError)?
+    {
+        let manga_id = row.manga_id;
+        if let Some(manga) = mangas.iter().find(|m| m.manga_id == manga_id) {
+            bookmarks.push(Bookmark {
+                manga_id,
+                manga: Arc::clone(manga),
+                page_id: row.page_id,
+                chapter_id: row.chapter_id,
+                page: row.page,
+                scroll: row.scroll,
+                image_url: row.image_url,
+                created_at: row.created_at,
+                percent: row.percent,
+                deleted_at: row.deleted_at,
+            });
+        } else {
+            continue;
+        }
+    }
+
+    let user = sqlx::query!(
+        r#"
+        SELECT
+            id, bookmarks_sync_timestamp
+        FROM
+            users
+        WHERE
+            id = $1
+    "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(UserBookmark {
+        bookmarks,
+        timestamp: match user.bookmarks_sync_timestamp {
+            Some(time) => {
+                if time == 0 {
+                    chrono::Utc::now().timestamp()
+                } else {
+                    time
+                }
+            }
+            None => chrono::Utc::now().timestamp(),
+        },
+    })
+}
+
+#[tracing::instrument(name = "update user_bookmark", skip_all)]
+pub async fn update_user_bookmarks(
+    pool: &PgPool,
+    user_id: i64,
+    user_bookmark: UserBookmark,
+    policy: MetadataPolicy,
+) -> Result<(), Error> {
+    let mut mangas_map: HashMap<i64, Arc<Manga>> = HashMap::new();
+    let mut tags_map: HashMap<i64, Arc<Tag>> = HashMap::new();
+    let mut manga_tags_set = HashSet::new();
+
+    for bookmark in &user_bookmark.bookmarks {
+        for tag in &bookmark.manga.tags {
+            tags_map.insert(tag.tag_id, Arc::clone(tag));
+
+            manga_tags_set.insert(MangaTagEntity {
+                manga_id: bookmark.manga_id,
+                tag_id: tag.tag_id,
+            });
+        }
+        mangas_map.insert(bookmark.manga_id, Arc::clone(&bookmark.manga));
+    }
+
+    let mut tx = pool.begin().await.map_err(DatabaseError::DatabaseError)?;
+
+    let tags_vec: Vec<Arc<Tag>> = tags_map.values().cloned().collect();
+    insert_tags(&mut tx, &tags_vec).await?;
+
+    let mangas_vec: Vec<Arc<Manga>> = mangas_map.values().cloned().collect();
+    insert_mangas(&mut tx, &mangas_vec, Some(user_id), policy).await?;
+
+    let manga_tags_vec: Vec<MangaTagEntity> = manga_tags_set.into_iter().collect();
+    insert_manga_tags(&mut tx, &manga_tags_vec).await?;
+
+    for batch in user_bookmark.bookmarks.chunks(200) {
+        let mut bookmarks_query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"
+            INSERT INTO bookmarks
+                (manga_id, page_id, chapter_id, page, scroll, image_url, created_at, percent, deleted_at, user_id)
+        "#,
+        );
+
+        bookmarks_query_builder.push_values(batch, |mut b, bookmark| {
+            b.push_bind(bookmark.manga_id)
+                .push_bind(bookmark.page_id)
+                .push_bind(bookmark.chapter_id)
+                .push_bind(bookmark.page)
+                .push_bind(bookmark.scroll)
+                .push_bind(&bookmark.image_url)
+                .push_bind(bookmark.created_at)
+                .push_bind(bookmark.percent)
+                .push_bind(bookmark.deleted_at)
+                .push_bind(user_id);
+        });
+        bookmarks_query_builder.push(
+            r#"
+            ON CONFLICT (user_id, manga_id, page_id)
+            DO UPDATE SET
+                chapter_id = EXCLUDED.chapter_id,
+                page = EXCLUDED.page,
+                scroll = EXCLUDED.scroll,
+                image_url = EXCLUDED.image_url,
+                created_at = EXCLUDED.created_at,
+                percent = EXCLUDED.percent,
+                deleted_at = EXCLUDED.deleted_at;
+        "#,
+        );
+
+        bookmarks_query_builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(DatabaseError::DatabaseError)?;
+    }
+
+    update_user_bookmark_sync_time(&mut tx, user_id).await?;
+
+    tx.commit().await.map_err(DatabaseError::DatabaseError)?;
+
+    drop(tags_map);
+    drop(mangas_map);
+    drop(manga_tags_vec);
+
+    drop(user_bookmark);
+
+    Ok(())
+}
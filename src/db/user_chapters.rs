@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::{
+    db::error::DatabaseError,
+    error::Error,
+    model::{ChapterRead, UserChapterRead},
+};
+
+use super::{
+    PostgresTransaction,
+    user::{update_user_chapter_sync_time, update_user_history_sync_time},
+};
+
+#[tracing::instrument(name = "get user_chapter", skip_all)]
+pub async fn get_user_chapters(pool: &PgPool, user_id: i64) -> Result<UserChapterRead, Error> {
+    let chapters = sqlx::query_as!(
+        ChapterRead,
+        r#"
+        SELECT
+            manga_id, chapter_id, read_at, percent, deleted_at
+        FROM
+            chapters_read
+        WHERE
+            user_id = $1
+    "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let user = sqlx::query!(
+        r#"
+        SELECT
+            id, chapters_sync_timestamp
+        FROM
+            users
+        WHERE
+            id = $1
+    "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(UserChapterRead {
+        chapters,
+        timestamp: match user.chapters_sync_timestamp {
+            Some(time) => {
+                if time == 0 {
+                    chrono::Utc::now().timestamp()
+                } else {
+                    time
+                }
+            }
+            None => chrono::Utc::now().timestamp(),
+        },
+    })
+}
+
+/// Merges the uploaded chapter reads. `read_at`, `percent` and `deleted_at` only ever move
+/// forward, a chapter counts as unread while its `deleted_at` is not older than `read_at`.
+#[tracing::instrument(name = "update user_chapter", skip_all)]
+pub async fn update_user_chapters(
+    pool: &PgPool,
+    user_id: i64,
+    user_chapter: UserChapterRead,
+) -> Result<(), Error> {
+    let mut chapters_map: HashMap<(i64, i64), ChapterRead> = HashMap::new();
+    for chapter in user_chapter.chapters {
+        chapters_map
+            .entry((chapter.manga_id, chapter.chapter_id))
+            .and_modify(|current| {
+                current.read_at = current.read_at.max(chapter.read_at);
+                current.percent = current.percent.max(chapter.percent);
+                current.deleted_at = current.deleted_at.max(chapter.deleted_at);
+            })
+            .or_insert(chapter);
+    }
+    let chapters_vec: Vec<ChapterRead> = chapters_map.into_values().collect();
+
+    let mut tx = pool.begin().await.map_err(DatabaseError::DatabaseError)?;
+
+    for batch in chapters_vec.chunks(200) {
+        let mut chapters_query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"
+            INSERT INTO chapters_read
+                (manga_id, chapter_id, read_at, percent, deleted_at, user_id)
+        "#,
+        );
+
+        chapters_query_builder.push_values(batch, |mut b, chapter| {
+            b.push_bind(chapter.manga_id)
+                .push_bind(chapter.chapter_id)
+                .push_bind(chapter.read_at)
+                .push_bind(chapter.percent)
+                .push_bind(chapter.deleted_at)
+                .push_bind(user_id);
+        });
+        chapters_query_builder.push(
+            r#"
+            ON CONFLICT (user_id, manga_id, chapter_id)
+            DO UPDATE SET
+                read_at = GREATEST(chapters_read.read_at, EXCLUDED.read_at),
+                percent = GREATEST(chapters_read.percent, EXCLUDED.percent),
+                deleted_at = GREATEST(chapters_read.deleted_at, EXCLUDED.deleted_at);
+        "#,
+        );
+
+        chapters_query_builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(DatabaseError::DatabaseError)?;
+    }
+
+    update_user_chapter_sync_time(&mut tx, user_id).await?;
+
+    let mut manga_ids: Vec<i64> = chapters_vec.iter().map(|c| c.manga_id).collect();
+    manga_ids.sort_unstable();
+    manga_ids.dedup();
+    if derive_user_history(&mut tx, user_id, &manga_ids).await? > 0 {
+        update_user_history_sync_time(&mut tx, user_id).await?;
+    }
+
+    tx.commit().await.map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Moves the `history` position of the given manga to their most recently read chapter, so
+/// clients that only sync `/resource/history` still see progress made through chapter reads.
+/// The server does not know the order of the chapters, so `percent` counts the chapters read
+/// before it plus its own progress, and is reset when the number of chapters is unknown.
+/// Returns the number of history entries updated.
+#[tracing::instrument(name = "derive user history", skip_all)]
+pub async fn derive_user_history(
+    tx: &mut PostgresTransaction,
+    user_id: i64,
+    manga_ids: &[i64],
+) -> Result<u64, Error> {
+    if manga_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE history
+        SET
+            chapter_id = latest.chapter_id,
+            updated_at = latest.read_at,
+            page = 0,
+            scroll = 0,
+            percent = CASE
+                WHEN history.chapters > 0
+                THEN LEAST((latest.read_count - 1 + latest.percent) / history.chapters, 1)
+                ELSE 0
+            END
+        FROM (
+            SELECT DISTINCT ON (manga_id)
+                manga_id, chapter_id, read_at, percent,
+                COUNT(*) OVER (PARTITION BY manga_id) AS read_count
+            FROM
+                chapters_read
+            WHERE
+                user_id = $1
+                AND manga_id = ANY($2)
+                AND deleted_at < read_at
+            ORDER BY manga_id, read_at DESC
+        ) AS latest
+        WHERE
+            history.user_id = $1
+            AND history.manga_id = latest.manga_id
+            AND history.deleted_at = 0
+            AND history.updated_at < latest.read_at
+            AND history.chapter_id <> latest.chapter_id;
+    "#,
+        user_id,
+        manga_ids
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(result.rows_affected())
+}
@@ -7,9 +7,12 @@ use futures::TryStreamExt;
 use sqlx::{PgPool, Postgres, QueryBuilder, Row};
 
 use crate::{
+    config::MetadataPolicy,
     db::error::DatabaseError,
     error::Error,
-    model::{Category, Favourite, Manga, MangaTag, MangaTagEntity, Tag, UserFavourite},
+    model::{
+        Category, ContentRating, Favourite, Manga, MangaTag, MangaTagEntity, Tag, UserFavourite,
+    },
 };
 
 use super::{
@@ -116,7 +119,7 @@ pub async fn get_user_favourites(pool: &PgPool, user_id: i64) -> Result<UserFavo
         SELECT
             id, title, alt_title,
             url, public_url, rating,
-            is_nsfw, cover_url, large_cover_url,
+            content_rating, content_level, cover_url, large_cover_url,
             state, author, source
         FROM
             mangas
@@ -151,12 +154,10 @@ pub async fn get_user_favourites(pool: &PgPool, user_id: i64) -> Result<UserFavo
             url: row.get("url"),
             public_url: row.get("public_url"),
             rating: row.get("rating"),
-            nsfw: if row.get("is_nsfw") { Some(1) } else { Some(0) },
-            content_rating: if row.get("is_nsfw") {
-                Some("ADULT".to_string())
-            } else {
-                None
-            },
+            nsfw: Some(u8::from(
+                row.get::<i16, _>("content_level") == ContentRating::Adult.level(),
+            )),
+            content_rating: row.get("content_rating"),
             cover_url: row.get("cover_url"),
             large_cover_url: row.get("large_cover_url"),
             state: row.get("state"),
@@ -237,6 +238,7 @@ pub async fn update_user_favourites(
     pool: &PgPool,
     user_id: i64,
     user_favourite: UserFavourite,
+    policy: MetadataPolicy,
 ) -> Result<(), Error> {
     let mut mangas_map: HashMap<i64, Arc<Manga>> = HashMap::new();
     let mut tags_map: HashMap<i64, Arc<Tag>> = HashMap::new();
@@ -260,7 +262,7 @@ pub async fn update_user_favourites(
     insert_tags(&mut tx, &tags_vec).await?;
 
     let mangas_vec: Vec<Arc<Manga>> = mangas_map.values().cloned().collect();
-    insert_mangas(&mut tx, &mangas_vec).await?;
+    insert_mangas(&mut tx, &mangas_vec, Some(user_id), policy).await?;
 
     let manga_tags_vec: Vec<MangaTagEntity> = manga_tags_set.into_iter().collect();
     insert_manga_tags(&mut tx, &manga_tags_vec).await?;
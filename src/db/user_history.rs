@@ -7,14 +7,18 @@ use futures::TryStreamExt;
 use sqlx::{PgPool, Postgres, QueryBuilder, Row};
 
 use crate::{
+    config::MetadataPolicy,
     db::error::DatabaseError,
     error::Error,
-    model::{History, Manga, MangaTag, MangaTagEntity, Tag, UserHistory},
+    model::{ContentRating, History, Manga, MangaTag, MangaTagEntity, Tag, UserHistory},
 };
 
 use super::{
-    manga::insert_mangas, manga_tags::insert_manga_tags, tags::insert_tags,
+    manga::{get_mangas_by_ids, insert_mangas},
+    manga_tags::insert_manga_tags,
+    tags::insert_tags,
     user::update_user_history_sync_time,
+    user_timeline::record_user_timeline,
 };
 
 #[tracing::instrument(name = "get user_history", skip_all)]
@@ -82,7 +86,7 @@ pub async fn get_user_history(pool: &PgPool, user_id: i64) -> Result<UserHistory
         SELECT
             id, title, alt_title,
             url, public_url, rating,
-            is_nsfw, cover_url, large_cover_url,
+            content_rating, content_level, cover_url, large_cover_url,
             state, author, source
         FROM
             mangas
@@ -117,12 +121,10 @@ pub async fn get_user_history(pool: &PgPool, user_id: i64) -> Result<UserHistory
             url: row.get("url"),
             public_url: row.get("public_url"),
             rating: row.get("rating"),
-            nsfw: if row.get("is_nsfw") { Some(1) } else { Some(0) },
-            content_rating: if row.get("is_nsfw") {
-                Some("ADULT".to_string())
-            } else {
-                None
-            },
+            nsfw: Some(u8::from(
+                row.get::<i16, _>("content_level") == ContentRating::Adult.level(),
+            )),
+            content_rating: row.get("content_rating"),
             cover_url: row.get("cover_url"),
             large_cover_url: row.get("large_cover_url"),
             state: row.get("state"),
@@ -208,6 +210,7 @@ pub async fn update_user_history(
     pool: &PgPool,
     user_id: i64,
     user_history: UserHistory,
+    policy: MetadataPolicy,
 ) -> Result<(), Error> {
     let mut mangas_map: HashMap<i64, Arc<Manga>> = HashMap::new();
     let mut tags_map: HashMap<i64, Arc<Tag>> = HashMap::new();
@@ -231,11 +234,13 @@ pub async fn update_user_history(
     insert_tags(&mut tx, &tags_vec).await?;
 
     let mangas_vec: Vec<Arc<Manga>> = mangas_map.values().cloned().collect();
-    insert_mangas(&mut tx, &mangas_vec).await?;
+    insert_mangas(&mut tx, &mangas_vec, Some(user_id), policy).await?;
 
     let manga_tags_vec: Vec<MangaTagEntity> = manga_tags_set.into_iter().collect();
     insert_manga_tags(&mut tx, &manga_tags_vec).await?;
 
+    record_user_timeline(&mut tx, user_id, &user_history.history).await?;
+
     for batch in user_history.history.chunks(200) {
         let mut history_query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
             r#"
@@ -290,3 +295,73 @@ pub async fn update_user_history(
 
     Ok(())
 }
+
+/// Most recently read history entries that are neither deleted nor finished.
+#[tracing::instrument(name = "get user continue reading", skip_all, fields(user_id))]
+pub async fn get_user_continue_reading(
+    pool: &PgPool,
+    user_id: i64,
+    limit: i64,
+) -> Result<Vec<History>, Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            manga_id, created_at, updated_at,
+            chapter_id, page, scroll,
+            percent, chapters, deleted_at
+        FROM
+            history
+        WHERE
+            user_id = $1
+            AND deleted_at = 0
+            AND percent < 1
+            -- Linked manga are one series, only its most recently read manga is listed.
+            AND NOT EXISTS (
+                SELECT 1
+                FROM
+                    manga_links
+                INNER JOIN
+                    manga_links AS linked ON linked.group_id = manga_links.group_id
+                        AND linked.manga_id <> manga_links.manga_id
+                INNER JOIN
+                    history AS newer ON newer.user_id = manga_links.user_id
+                        AND newer.manga_id = linked.manga_id
+                WHERE
+                    manga_links.user_id = $1
+                    AND manga_links.manga_id = history.manga_id
+                    AND newer.deleted_at = 0
+                    AND newer.updated_at > history.updated_at
+            )
+        ORDER BY updated_at DESC
+        LIMIT $2
+    "#,
+        user_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let manga_ids: Vec<i64> = rows.iter().map(|row| row.manga_id).collect();
+    let mangas = get_mangas_by_ids(pool, &manga_ids).await?;
+
+    let mut history = Vec::new();
+    for row in rows {
+        if let Some(manga) = mangas.iter().find(|m| m.manga_id == row.manga_id) {
+            history.push(History {
+                manga_id: row.manga_id,
+                manga: Arc::clone(manga),
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                chapter_id: row.chapter_id,
+                page: row.page,
+                scroll: row.scroll,
+                percent: row.percent,
+                chapters: row.chapters,
+                deleted_at: row.deleted_at,
+            });
+        }
+    }
+
+    Ok(history)
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::{
+    db::error::DatabaseError,
+    error::Error,
+    model::{Setting, UserSetting},
+};
+
+use super::user::update_user_setting_sync_time;
+
+#[tracing::instrument(name = "get user_setting", skip_all, fields(namespace))]
+pub async fn get_user_settings(
+    pool: &PgPool,
+    user_id: i64,
+    namespace: Option<String>,
+) -> Result<UserSetting, Error> {
+    let settings = sqlx::query!(
+        r#"
+        SELECT
+            namespace, "key", value, updated_at, deleted_at
+        FROM
+            settings
+        WHERE
+            user_id = $1
+            AND ($2::text IS NULL OR namespace = $2)
+    "#,
+        user_id,
+        namespace
+    )
+    .map(|row| Setting {
+        namespace: row.namespace,
+        key: row.key,
+        value: row.value,
+        updated_at: row.updated_at,
+        deleted_at: row.deleted_at,
+    })
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let user = sqlx::query!(
+        r#"
+        SELECT
+            id, settings_sync_timestamp
+        FROM
+            users
+        WHERE
+            id = $1
+    "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(UserSetting {
+        settings,
+        timestamp: match user.settings_sync_timestamp {
+            Some(time) => {
+                if time == 0 {
+                    chrono::Utc::now().timestamp()
+                } else {
+                    time
+                }
+            }
+            None => chrono::Utc::now().timestamp(),
+        },
+    })
+}
+
+/// Merges the uploaded settings, a key is only overwritten by a newer `updated_at`.
+#[tracing::instrument(name = "update user_setting", skip_all)]
+pub async fn update_user_settings(
+    pool: &PgPool,
+    user_id: i64,
+    user_setting: UserSetting,
+) -> Result<(), Error> {
+    let mut settings_map: HashMap<(&str, &str), &Setting> = HashMap::new();
+    for setting in &user_setting.settings {
+        let key = (setting.namespace.as_str(), setting.key.as_str());
+        match settings_map.get(&key) {
+            Some(current) if current.updated_at >= setting.updated_at => {}
+            _ => {
+                settings_map.insert(key, setting);
+            }
+        }
+    }
+    let settings_vec: Vec<&Setting> = settings_map.into_values().collect();
+
+    let mut tx = pool.begin().await.map_err(DatabaseError::DatabaseError)?;
+
+    for batch in settings_vec.chunks(200) {
+        let mut settings_query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"
+            INSERT INTO settings
+                (namespace, "key", value, updated_at, deleted_at, user_id)
+        "#,
+        );
+
+        settings_query_builder.push_values(batch, |mut b, setting| {
+            b.push_bind(&setting.namespace)
+                .push_bind(&setting.key)
+                .push_bind(&setting.value)
+                .push_bind(setting.updated_at)
+                .push_bind(setting.deleted_at)
+                .push_bind(user_id);
+        });
+        settings_query_builder.push(
+            r#"
+            ON CONFLICT (user_id, namespace, "key")
+            DO UPDATE SET
+                value = EXCLUDED.value,
+                updated_at = EXCLUDED.updated_at,
+                deleted_at = EXCLUDED.deleted_at
+            WHERE
+                settings.updated_at < EXCLUDED.updated_at;
+        "#,
+        );
+
+        settings_query_builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(DatabaseError::DatabaseError)?;
+    }
+
+    update_user_setting_sync_time(&mut tx, user_id).await?;
+
+    tx.commit().await.map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
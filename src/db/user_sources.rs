@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::{
+    db::error::DatabaseError,
+    error::Error,
+    model::{Source, UserSource},
+};
+
+use super::user::update_user_source_sync_time;
+
+#[tracing::instrument(name = "get user_source", skip_all)]
+pub async fn get_user_sources(pool: &PgPool, user_id: i64) -> Result<UserSource, Error> {
+    let sources = sqlx::query!(
+        r#"
+        SELECT
+            source, enabled, pinned, sort_key, added_in, used_at, deleted_at
+        FROM
+            sources
+        WHERE
+            user_id = $1
+    "#,
+        user_id
+    )
+    .map(|row| Source {
+        source: row.source,
+        enabled: if row.enabled { 1 } else { 0 },
+        pinned: if row.pinned { 1 } else { 0 },
+        sort_key: row.sort_key,
+        added_in: row.added_in,
+        used_at: row.used_at,
+        deleted_at: row.deleted_at,
+    })
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let user = sqlx::query!(
+        r#"
+        SELECT
+            id, sources_sync_timestamp
+        FROM
+            users
+        WHERE
+            id = $1
+    "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(UserSource {
+        sources,
+        timestamp: match user.sources_sync_timestamp {
+            Some(time) => {
+                if time == 0 {
+                    chrono::Utc::now().timestamp()
+                } else {
+                    time
+                }
+            }
+            None => chrono::Utc::now().timestamp(),
+        },
+    })
+}
+
+/// Stores the uploaded sources, a source listed more than once keeps its most recently used
+/// or deleted entry.
+#[tracing::instrument(name = "update user_source", skip_all)]
+pub async fn update_user_sources(
+    pool: &PgPool,
+    user_id: i64,
+    user_source: UserSource,
+) -> Result<(), Error> {
+    let mut sources_map: HashMap<&str, &Source> = HashMap::new();
+    for source in &user_source.sources {
+        match sources_map.get(source.source.as_str()) {
+            Some(current) if changed_at(current) > changed_at(source) => {}
+            _ => {
+                sources_map.insert(&source.source, source);
+            }
+        }
+    }
+    let sources_vec: Vec<&Source> = sources_map.into_values().collect();
+
+    let mut tx = pool.begin().await.map_err(DatabaseError::DatabaseError)?;
+
+    for batch in sources_vec.chunks(200) {
+        let mut sources_query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"
+            INSERT INTO sources
+                (source, enabled, pinned, sort_key, added_in, used_at, deleted_at, user_id)
+        "#,
+        );
+
+        sources_query_builder.push_values(batch, |mut b, source| {
+            b.push_bind(&source.source)
+                .push_bind(source.enabled != 0)
+                .push_bind(source.pinned != 0)
+                .push_bind(source.sort_key)
+                .push_bind(source.added_in)
+                .push_bind(source.used_at)
+                .push_bind(source.deleted_at)
+                .push_bind(user_id);
+        });
+        sources_query_builder.push(
+            r#"
+            ON CONFLICT (user_id, source)
+            DO UPDATE SET
+                enabled = EXCLUDED.enabled,
+                pinned = EXCLUDED.pinned,
+                sort_key = EXCLUDED.sort_key,
+                added_in = EXCLUDED.added_in,
+                used_at = EXCLUDED.used_at,
+                deleted_at = EXCLUDED.deleted_at;
+        "#,
+        );
+
+        sources_query_builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(DatabaseError::DatabaseError)?;
+    }
+
+    update_user_source_sync_time(&mut tx, user_id).await?;
+
+    tx.commit().await.map_err(DatabaseError::DatabaseError)?;
+
+    Ok(())
+}
+
+fn changed_at(source: &Source) -> i64 {
+    source.used_at.max(source.deleted_at)
+}
@@ -0,0 +1,138 @@
+use std::{collections::HashMap, sync::Arc};
+
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::{
+    db::error::DatabaseError,
+    error::Error,
+    model::{History, TimelineEntry},
+};
+
+use super::{PostgresTransaction, manga::get_mangas_by_ids};
+
+#[tracing::instrument(name = "get user_timeline", skip_all, fields(user_id))]
+pub async fn get_user_timeline(
+    pool: &PgPool,
+    user_id: i64,
+    from: Option<i64>,
+    to: Option<i64>,
+    limit: i64,
+) -> Result<Vec<TimelineEntry>, Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            manga_id, chapter_id, page, percent, recorded_at
+        FROM
+            history_timeline
+        WHERE
+            user_id = $1
+            AND ($2::bigint IS NULL OR recorded_at >= $2)
+            AND ($3::bigint IS NULL OR recorded_at < $3)
+        ORDER BY recorded_at DESC, id DESC
+        LIMIT $4
+    "#,
+        user_id,
+        from,
+        to,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    let mut manga_ids: Vec<i64> = rows.iter().map(|row| row.manga_id).collect();
+    manga_ids.sort_unstable();
+    manga_ids.dedup();
+    let mangas = get_mangas_by_ids(pool, &manga_ids).await?;
+
+    let mut timeline = Vec::new();
+    for row in rows {
+        if let Some(manga) = mangas.iter().find(|m| m.manga_id == row.manga_id) {
+            timeline.push(TimelineEntry {
+                manga_id: row.manga_id,
+                manga: Arc::clone(manga),
+                chapter_id: row.chapter_id,
+                page: row.page,
+                percent: row.percent,
+                recorded_at: row.recorded_at,
+            });
+        }
+    }
+
+    Ok(timeline)
+}
+
+/// Appends a timeline entry for every uploaded history entry whose reading position moved
+/// forward compared to what is stored. Must run before the history itself is updated.
+#[tracing::instrument(name = "record user_timeline", skip_all)]
+pub async fn record_user_timeline(
+    tx: &mut PostgresTransaction,
+    user_id: i64,
+    history: &[History],
+) -> Result<(), Error> {
+    let manga_ids: Vec<i64> = history.iter().map(|his| his.manga_id).collect();
+    if manga_ids.is_empty() {
+        return Ok(());
+    }
+
+    let current: HashMap<i64, _> = sqlx::query!(
+        r#"
+        SELECT
+            manga_id, chapter_id, page, percent, updated_at
+        FROM
+            history
+        WHERE
+            user_id = $1
+            AND manga_id = ANY($2)
+    "#,
+        user_id,
+        &manga_ids
+    )
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(DatabaseError::DatabaseError)?
+    .into_iter()
+    .map(|row| (row.manga_id, row))
+    .collect();
+
+    let progressed: Vec<&History> = history
+        .iter()
+        .filter(|his| his.deleted_at == 0)
+        .filter(|his| match current.get(&his.manga_id) {
+            Some(row) => {
+                // `percent` is the position in the whole manga, so going back to an earlier
+                // chapter lowers it.
+                his.updated_at > row.updated_at
+                    && (his.percent > row.percent
+                        || (his.chapter_id == row.chapter_id && his.page > row.page))
+            }
+            None => true,
+        })
+        .collect();
+
+    for batch in progressed.chunks(200) {
+        let mut timeline_query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"
+            INSERT INTO history_timeline
+                (manga_id, chapter_id, page, percent, recorded_at, user_id)
+        "#,
+        );
+
+        timeline_query_builder.push_values(batch, |mut b, his| {
+            b.push_bind(his.manga_id)
+                .push_bind(his.chapter_id)
+                .push_bind(his.page)
+                .push_bind(his.percent)
+                .push_bind(his.updated_at)
+                .push_bind(user_id);
+        });
+
+        timeline_query_builder
+            .build()
+            .execute(&mut **tx)
+            .await
+            .map_err(DatabaseError::DatabaseError)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use futures::TryStreamExt;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::{
+    config::MetadataPolicy,
+    db::error::DatabaseError,
+    error::Error,
+    model::{Manga, MangaTagEntity, Tag, Track, UserTrack},
+};
+
+use super::{
+    manga::{get_mangas_by_ids, insert_mangas},
+    manga_tags::insert_manga_tags,
+    tags::insert_tags,
+    user::update_user_track_sync_time,
+};
+
+#[tracing::instrument(name = "get user_track", skip_all)]
+pub async fn get_user_tracks(pool: &PgPool, user_id: i64) -> Result<UserTrack, Error> {
+    let manga_ids = sqlx::query_scalar!(
+        r#"
+        SELECT
+            manga_id
+        FROM
+            tracks
+        WHERE
+            user_id = $1
+    "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    if manga_ids.is_empty() {
+        return Ok(UserTrack {
+            tracks: vec![],
+            timestamp: chrono::Utc::now().timestamp(),
+        });
+    }
+
+    let mangas = get_mangas_by_ids(pool, &manga_ids).await?;
+
+    let mut tracks_stream = sqlx::query!(
+        r#"
+        SELECT
+            manga_id, last_chapter_id, chapters_total,
+            chapters_new, last_check_at, last_chapter_date,
+            updated_at, deleted_at
+        FROM
+            tracks
+        WHERE
+            user_id = $1
+    "#,
+        user_id
+    )
+    .fetch(pool);
+
+    let mut tracks = Vec::new();
+    while let Some(row) = tracks_stream
+        .try_next()
+        .await
+        .map_err(DatabaseError::DatabaseError)?
+    {
+        let manga_id = row.manga_id;
+        if let Some(manga) = mangas.iter().find(|m| m.manga_id == manga_id) {
+            tracks.push(Track {
+                manga_id,
+                manga: Arc::clone(manga),
+                last_chapter_id: row.last_chapter_id,
+                chapters_total: row.chapters_total,
+                chapters_new: row.chapters_new,
+                last_check_at: row.last_check_at,
+                last_chapter_date: row.last_chapter_date,
+                updated_at: row.updated_at,
+                deleted_at: row.deleted_at,
+            });
+        } else {
+            continue;
+        }
+    }
+
+    let user = sqlx::query!(
+        r#"
+        SELECT
+            id, tracks_sync_timestamp
+        FROM
+            users
+        WHERE
+            id = $1
+    "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(DatabaseError::DatabaseError)?;
+
+    Ok(UserTrack {
+        tracks,
+        timestamp: match user.tracks_sync_timestamp {
+            Some(time) => {
+                if time == 0 {
+                    chrono::Utc::now().timestamp()
+                } else {
+                    time
+                }
+            }
+            None => chrono::Utc::now().timestamp(),
+        },
+    })
+}
+
+/// Merges the uploaded tracker state, a manga is only overwritten by a newer `updated_at`.
+#[tracing::instrument(name = "update user_track", skip_all)]
+pub async fn update_user_tracks(
+    pool: &PgPool,
+    user_id: i64,
+    user_track: UserTrack,
+    policy: MetadataPolicy,
+) -> Result<(), Error> {
+    let mut mangas_map: HashMap<i64, Arc<Manga>> = HashMap::new();
+    let mut tags_map: HashMap<i64, Arc<Tag>> = HashMap::new();
+    let mut manga_tags_set = HashSet::new();
+    let mut tracks_map: HashMap<i64, &Track> = HashMap::new();
+
+    for track in &user_track.tracks {
+        for tag in &track.manga.tags {
+            tags_map.insert(tag.tag_id, Arc::clone(tag));
+
+            manga_tags_set.insert(MangaTagEntity {
+                manga_id: track.manga_id,
+                tag_id: tag.tag_id,
+            });
+        }
+        mangas_map.insert(track.manga_id, Arc::clone(&track.manga));
+
+        match tracks_map.get(&track.manga_id) {
+            Some(current) if current.updated_at >= track.updated_at => {}
+            _ => {
+                tracks_map.insert(track.manga_id, track);
+            }
+        }
+    }
+
+    let mut tx = pool.begin().await.map_err(DatabaseError::DatabaseError)?;
+
+    let tags_vec: Vec<Arc<Tag>> = tags_map.values().cloned().collect();
+    insert_tags(&mut tx, &tags_vec).await?;
+
+    let mangas_vec: Vec<Arc<Manga>> = mangas_map.values().cloned().collect();
+    insert_mangas(&mut tx, &mangas_vec, Some(user_id), policy).await?;
+
+    let manga_tags_vec: Vec<MangaTagEntity> = manga_tags_set.into_iter().collect();
+    insert_manga_tags(&mut tx, &manga_tags_vec).await?;
+
+    let tracks_vec: Vec<&Track> = tracks_map.into_values().collect();
+    for batch in tracks_vec.chunks(200) {
+        let mut tracks_query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"
+            INSERT INTO tracks
+                (manga_id, last_chapter_id, chapters_total, chapters_new, last_check_at, last_chapter_date, updated_at, deleted_at, user_id)
+        "#,
+        );
+
+        tracks_query_builder.push_values(batch, |mut b, track| {
+            b.push_bind(track.manga_id)
+                .push_bind(track.last_chapter_id)
+                .push_bind(track.chapters_total)
+                .push_bind(track.chapters_new)
+                .push_bind(track.last_check_at)
+                .push_bind(track.last_chapter_date)
+                .push_bind(track.updated_at)
+                .push_bind(track.deleted_at)
+                .push_bind(user_id);
+        });
+        tracks_query_builder.push(
+            r#"
+            ON CONFLICT (user_id, manga_id)
+            DO UPDATE SET
+                last_chapter_id = EXCLUDED.last_chapter_id,
+                chapters_total = EXCLUDED.chapters_total,
+                chapters_new = EXCLUDED.chapters_new,
+                last_check_at = EXCLUDED.last_check_at,
+                last_chapter_date = EXCLUDED.last_chapter_date,
+                updated_at = EXCLUDED.updated_at,
+                deleted_at = EXCLUDED.deleted_at
+            WHERE
+                tracks.updated_at < EXCLUDED.updated_at;
+        "#,
+        );
+
+        tracks_query_builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(DatabaseError::DatabaseError)?;
+    }
+
+    update_user_track_sync_time(&mut tx, user_id).await?;
+
+    tx.commit().await.map_err(DatabaseError::DatabaseError)?;
+
+    drop(tags_map);
+    drop(mangas_map);
+    drop(manga_tags_vec);
+
+    drop(user_track);
+
+    Ok(())
+}
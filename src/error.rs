@@ -1,7 +1,10 @@
 use axum::{http::StatusCode, response::IntoResponse};
 use validator::ValidationErrors;
 
-use crate::{auth::error::AuthError, db::error::DatabaseError};
+use crate::{
+    auth::error::AuthError, backup::error::BackupError, covers::error::CoverError,
+    db::error::DatabaseError,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -14,6 +17,12 @@ pub enum Error {
     #[error("Validation error")]
     Validation(ValidationErrors),
 
+    #[error("Backup error")]
+    Backup(BackupError),
+
+    #[error("Cover error")]
+    Cover(CoverError),
+
     #[error("Other error: {0}")]
     Other(anyhow::Error),
 }
@@ -54,6 +63,25 @@ impl IntoResponse for Error {
                 }
                 AuthError::UserNotFound => StatusCode::UNAUTHORIZED.into_response(),
                 AuthError::IncorrectCredential => StatusCode::UNAUTHORIZED.into_response(),
+                AuthError::AccountDisabled => StatusCode::FORBIDDEN.into_response(),
+                AuthError::Forbidden => StatusCode::FORBIDDEN.into_response(),
+            },
+            Error::Backup(backup_error) => {
+                tracing::error!(err.msg = %backup_error, err.details=?backup_error, "Backup Error");
+
+                (StatusCode::BAD_REQUEST, backup_error.to_string()).into_response()
+            }
+            Error::Cover(cover_error) => match cover_error {
+                CoverError::Storage(error) => {
+                    tracing::error!(err.msg = %error, err.details=?error, "Cover Storage Error");
+
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+                cover_error => {
+                    tracing::warn!(err.msg = %cover_error, err.details=?cover_error, "Cover Error");
+
+                    StatusCode::BAD_GATEWAY.into_response()
+                }
             },
             Error::Other(error) => {
                 tracing::error!(err.msg = %error, err.details=?error, "Other Error");
@@ -1,17 +1,25 @@
+use std::time::Duration;
+
 use anyhow::Context;
 use axum::serve;
 use config::Config;
+use covers::cleanup_job;
 use routes::init_router;
 use state::AppState;
 use tokio::net::TcpListener;
 
 pub mod auth;
+pub mod backup;
+pub mod cache;
 pub mod config;
 pub mod controllers;
+pub mod covers;
 pub mod db;
 pub mod error;
+pub mod linking;
 pub mod middlewares;
 pub mod model;
+pub mod pagination;
 pub mod routes;
 pub mod state;
 pub mod telemetry;
@@ -20,6 +28,12 @@ pub async fn run() -> Result<(), anyhow::Error> {
     let config = Config::new().context("Failed to read configuration.")?;
     let address = config.application.get_address();
     let state = AppState::init(config).await?;
+    if let Some(covers) = &state.covers
+        && state.config.covers.cleanup_interval > 0
+    {
+        let interval = Duration::from_secs(state.config.covers.cleanup_interval);
+        tokio::spawn(cleanup_job(covers.clone(), state.pool.clone(), interval));
+    }
     let router = init_router(state);
 
     tracing::info!("Starting server: {}", address);
@@ -0,0 +1,228 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// A manga of the user's library considered for cross-source linking.
+#[derive(Debug, Clone)]
+pub struct LinkCandidate {
+    pub manga_id: i64,
+    pub title: String,
+    pub alt_title: Option<String>,
+    pub author: Option<String>,
+    pub source: String,
+    /// Link group the user already put the manga in.
+    pub group_id: Option<i64>,
+}
+
+/// Lowercases and keeps only letters and digits, separated by single spaces.
+pub fn normalize_title(value: &str) -> String {
+    value
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Like [`normalize_title`] with the words sorted, so "Oda Eiichiro" matches "Eiichiro Oda".
+pub fn normalize_author(value: &str) -> String {
+    let normalized = normalize_title(value);
+    let mut words: Vec<&str> = normalized.split(' ').collect();
+    words.sort_unstable();
+
+    words.join(" ")
+}
+
+/// Groups manga of different sources sharing a normalized title or alt title, unless the group
+/// would end up with two different known authors. Groups already confirmed as a whole are left
+/// out. Each group is sorted by manga id.
+pub fn suggest_groups(candidates: &[LinkCandidate]) -> Vec<Vec<i64>> {
+    // Known author of each group, kept at its root.
+    let mut authors: Vec<Option<String>> = candidates
+        .iter()
+        .map(|candidate| {
+            candidate
+                .author
+                .as_deref()
+                .map(normalize_author)
+                .filter(|author| !author.is_empty())
+        })
+        .collect();
+
+    // Which author a manga without one ends up with depends on the order of the unions, so
+    // titles and manga are walked in a fixed order to suggest the same groups every time.
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by_key(|&index| candidates[index].manga_id);
+
+    let mut by_title: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for index in order {
+        let candidate = &candidates[index];
+        let mut titles = vec![normalize_title(&candidate.title)];
+        if let Some(alt_title) = &candidate.alt_title {
+            titles.push(normalize_title(alt_title));
+        }
+        titles.sort_unstable();
+        titles.dedup();
+
+        for title in titles.into_iter().filter(|title| !title.is_empty()) {
+            by_title.entry(title).or_default().push(index);
+        }
+    }
+
+    let mut parents: Vec<usize> = (0..candidates.len()).collect();
+    for indexes in by_title.values() {
+        for (position, &a) in indexes.iter().enumerate() {
+            for &b in &indexes[position + 1..] {
+                if candidates[a].source != candidates[b].source {
+                    union(&mut parents, &mut authors, a, b);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in 0..candidates.len() {
+        let root = find(&mut parents, index);
+        groups.entry(root).or_default().push(index);
+    }
+
+    let mut suggestions: Vec<Vec<i64>> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .filter(|members| {
+            let group_id = candidates[members[0]].group_id;
+            group_id.is_none()
+                || members
+                    .iter()
+                    .any(|&member| candidates[member].group_id != group_id)
+        })
+        .map(|members| {
+            let mut manga_ids: Vec<i64> = members
+                .into_iter()
+                .map(|member| candidates[member].manga_id)
+                .collect();
+            manga_ids.sort_unstable();
+            manga_ids
+        })
+        .collect();
+    suggestions.sort_unstable();
+
+    suggestions
+}
+
+fn find(parents: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        root = parents[root];
+    }
+
+    let mut current = index;
+    while parents[current] != root {
+        let next = parents[current];
+        parents[current] = root;
+        current = next;
+    }
+
+    root
+}
+
+/// Merges the groups of `a` and `b`, unless their known authors differ. Comparing the groups
+/// rather than the two manga keeps a manga without author from bridging two authors.
+fn union(parents: &mut [usize], authors: &mut [Option<String>], a: usize, b: usize) {
+    let a = find(parents, a);
+    let b = find(parents, b);
+    let compatible_authors = match (&authors[a], &authors[b]) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    };
+    if a != b && compatible_authors {
+        parents[b] = a;
+        if authors[a].is_none() {
+            authors[a] = authors[b].take();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LinkCandidate, normalize_author, normalize_title, suggest_groups};
+
+    fn candidate(manga_id: i64, title: &str, author: Option<&str>, source: &str) -> LinkCandidate {
+        LinkCandidate {
+            manga_id,
+            title: title.to_string(),
+            alt_title: None,
+            author: author.map(str::to_string),
+            source: source.to_string(),
+            group_id: None,
+        }
+    }
+
+    #[test]
+    fn normalize_should_ignore_case_and_punctuation() {
+        assert_eq!(normalize_title("  One-Piece!! "), "one piece");
+        assert_eq!(normalize_title("Ｏｎｅ Piece"), "ｏｎｅ piece");
+        assert_eq!(
+            normalize_author("ODA, Eiichiro"),
+            normalize_author("Eiichiro Oda")
+        );
+    }
+
+    #[test]
+    fn suggest_groups_should_link_across_sources() {
+        let mut alt = candidate(4, "Wan Pisu", None, "C");
+        alt.alt_title = Some("One Piece".to_string());
+
+        let candidates = vec![
+            candidate(1, "One Piece", Some("Oda Eiichiro"), "A"),
+            candidate(2, "one-piece", Some("Eiichiro Oda"), "B"),
+            candidate(3, "One Piece", Some("Someone Else"), "C"),
+            alt,
+            // Same source, never a suggestion on its own.
+            candidate(5, "Berserk", None, "A"),
+            candidate(6, "Berserk", None, "A"),
+        ];
+
+        assert_eq!(suggest_groups(&candidates), vec![vec![1, 2, 4]]);
+    }
+
+    #[test]
+    fn suggest_groups_should_not_bridge_authors() {
+        let candidates = vec![
+            candidate(1, "Berserk", Some("Miura Kentaro"), "A"),
+            candidate(2, "Berserk", None, "B"),
+            candidate(3, "Berserk", Some("Someone Else"), "C"),
+        ];
+
+        assert_eq!(suggest_groups(&candidates), vec![vec![1, 2]]);
+    }
+
+    #[test]
+    fn suggest_groups_should_be_stable() {
+        let mut bridge = candidate(2, "Alpha", None, "B");
+        bridge.alt_title = Some("Beta".to_string());
+
+        let mut candidates = vec![
+            candidate(1, "Alpha", Some("Someone"), "A"),
+            bridge,
+            candidate(3, "Beta", Some("Someone Else"), "C"),
+        ];
+
+        for _ in 0..10 {
+            assert_eq!(suggest_groups(&candidates), vec![vec![1, 2]]);
+            candidates.reverse();
+        }
+    }
+
+    #[test]
+    fn suggest_groups_should_skip_confirmed_groups() {
+        let mut candidates = vec![
+            candidate(1, "Berserk", None, "A"),
+            candidate(2, "Berserk", None, "B"),
+        ];
+        candidates[0].group_id = Some(7);
+        candidates[1].group_id = Some(7);
+        assert!(suggest_groups(&candidates).is_empty());
+
+        candidates.push(candidate(3, "Berserk", None, "C"));
+        assert_eq!(suggest_groups(&candidates), vec![vec![1, 2, 3]]);
+    }
+}
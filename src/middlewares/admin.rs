@@ -0,0 +1,20 @@
+use std::sync::Arc;
+
+use axum::{body::Body, extract::Request, middleware::Next, response::Response};
+
+use crate::{auth::error::AuthError, error::Error, model::User};
+
+/// Must be layered inside `jwt_auth_middleware`, which provides the `User` extension.
+#[tracing::instrument(name = "[MIDDLEWARE] admin", skip_all)]
+pub async fn admin_middleware(req: Request, next: Next) -> Result<Response<Body>, Error> {
+    let is_admin = req
+        .extensions()
+        .get::<Arc<User>>()
+        .is_some_and(|user| user.is_admin);
+
+    if !is_admin {
+        return Err(Error::Auth(AuthError::Forbidden));
+    }
+
+    Ok(next.run(req).await)
+}
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use axum::{
     body::Body,
     extract::{Request, State},
+    http::HeaderMap,
     middleware::Next,
     response::Response,
 };
@@ -11,6 +12,7 @@ use crate::{
     auth::{decode_jwt, error::AuthError},
     db::user::get_user_by_id_optional,
     error::Error,
+    model::User,
     state::SharedAppState,
 };
 
@@ -20,7 +22,36 @@ pub async fn jwt_auth_middleware(
     mut req: Request,
     next: Next,
 ) -> Result<Response<Body>, Error> {
-    let auth_header = match req.headers_mut().get(axum::http::header::AUTHORIZATION) {
+    let user = authenticate(&app_state, req.headers()).await?;
+    req.extensions_mut().insert(user);
+
+    Ok(next.run(req).await)
+}
+
+/// Like `jwt_auth_middleware` when a token is sent. Requests without one are only let
+/// through anonymously in global catalog mode.
+#[tracing::instrument(name = "[MIDDLEWARE] catalog auth", skip_all)]
+pub async fn catalog_auth_middleware(
+    State(app_state): State<SharedAppState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response<Body>, Error> {
+    if app_state.config.application.global_catalog
+        && !req
+            .headers()
+            .contains_key(axum::http::header::AUTHORIZATION)
+    {
+        return Ok(next.run(req).await);
+    }
+
+    let user = authenticate(&app_state, req.headers()).await?;
+    req.extensions_mut().insert(user);
+
+    Ok(next.run(req).await)
+}
+
+async fn authenticate(app_state: &SharedAppState, headers: &HeaderMap) -> Result<Arc<User>, Error> {
+    let auth_header = match headers.get(axum::http::header::AUTHORIZATION) {
         Some(header) => header.to_str().map_err(|e| Error::Other(e.into()))?,
         None => {
             return Err(Error::Auth(AuthError::Unauthenticated));
@@ -60,7 +91,9 @@ pub async fn jwt_auth_middleware(
         }
     };
 
-    req.extensions_mut().insert(user);
+    if user.is_disabled {
+        return Err(Error::Auth(AuthError::AccountDisabled));
+    }
 
-    Ok(next.run(req).await)
+    Ok(user)
 }
@@ -1,3 +1,5 @@
+pub mod admin;
 pub mod jwt_auth;
 
-pub use jwt_auth::jwt_auth_middleware;
+pub use admin::admin_middleware;
+pub use jwt_auth::{catalog_auth_middleware, jwt_auth_middleware};
@@ -1,10 +1,68 @@
 use std::sync::Arc;
+use validator::Validate;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct User {
     pub id: i64,
     pub email: String,
     pub nickname: Option<String>,
+    pub is_admin: bool,
+    pub is_disabled: bool,
+    /// Whether the user's favourites feed other users' recommendations.
+    pub share_reading_signals: bool,
+    /// Highest rating of the manga listed outside the user's synced data, the server default
+    /// when `None`.
+    pub max_content_rating: Option<ContentRating>,
+}
+
+impl User {
+    pub fn max_content_rating(&self, default: ContentRating) -> ContentRating {
+        self.max_content_rating.unwrap_or(default)
+    }
+}
+
+/// Content rating levels, from least to most restricted. The level is what gets stored, in
+/// `users.max_content_rating` and the generated `mangas.content_level`: 0 safe, 1 suggestive,
+/// 2 adult.
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    sqlx::Type,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[repr(i16)]
+pub enum ContentRating {
+    Safe = 0,
+    Suggestive = 1,
+    #[default]
+    Adult = 2,
+}
+
+impl ContentRating {
+    pub fn level(self) -> i16 {
+        self as i16
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct UserSummary {
+    pub id: i64,
+    pub email: String,
+    pub nickname: Option<String>,
+    pub is_admin: bool,
+    pub is_disabled: bool,
+    pub favourites_count: i64,
+    pub history_count: i64,
+    pub favourites_sync_timestamp: Option<i64>,
+    pub history_sync_timestamp: Option<i64>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -93,3 +151,193 @@ pub struct UserHistory {
     pub history: Vec<History>,
     pub timestamp: i64,
 }
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ChapterRead {
+    pub manga_id: i64,
+    pub chapter_id: i64,
+    pub read_at: i64,
+    pub percent: f32,
+    pub deleted_at: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct UserChapterRead {
+    pub chapters: Vec<ChapterRead>,
+    pub timestamp: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Bookmark {
+    pub manga_id: i64,
+    pub manga: Arc<Manga>,
+    pub page_id: i64,
+    pub chapter_id: i64,
+    pub page: i32,
+    pub scroll: i32,
+    pub image_url: String,
+    pub created_at: i64,
+    pub percent: f32,
+    pub deleted_at: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct UserBookmark {
+    pub bookmarks: Vec<Bookmark>,
+    pub timestamp: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Track {
+    pub manga_id: i64,
+    pub manga: Arc<Manga>,
+    pub last_chapter_id: i64,
+    pub chapters_total: i32,
+    pub chapters_new: i32,
+    pub last_check_at: i64,
+    pub last_chapter_date: i64,
+    pub updated_at: i64,
+    pub deleted_at: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct UserTrack {
+    pub tracks: Vec<Track>,
+    pub timestamp: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Validate)]
+pub struct Source {
+    #[validate(length(min = 1, max = 120))]
+    pub source: String,
+    pub enabled: u8,
+    pub pinned: u8,
+    pub sort_key: i32,
+    pub added_in: i32,
+    pub used_at: i64,
+    pub deleted_at: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Validate)]
+pub struct UserSource {
+    #[validate(nested)]
+    pub sources: Vec<Source>,
+    pub timestamp: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Validate)]
+pub struct Setting {
+    #[validate(length(min = 1, max = 120))]
+    pub namespace: String,
+    #[validate(length(min = 1, max = 255))]
+    pub key: String,
+    pub value: serde_json::Value,
+    pub updated_at: i64,
+    pub deleted_at: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Validate)]
+pub struct UserSetting {
+    #[validate(nested)]
+    pub settings: Vec<Setting>,
+    pub timestamp: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ReadingSummary {
+    pub started: i64,
+    pub finished: i64,
+    pub completion_rate: f64,
+    pub chapters_read: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct WeeklyChapters {
+    /// Start of the week (Monday, UTC) in milliseconds.
+    pub week: i64,
+    pub chapters: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct TagStat {
+    pub tag: Tag,
+    pub count: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct SourceStat {
+    pub source: String,
+    pub count: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct TimelineEntry {
+    pub manga_id: i64,
+    pub manga: Arc<Manga>,
+    pub chapter_id: i64,
+    pub page: i16,
+    pub percent: f32,
+    pub recorded_at: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct PublicList {
+    pub category_id: i64,
+    pub title: String,
+    pub slug: String,
+    pub hide_nsfw: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct SharedList {
+    pub title: String,
+    pub nickname: Option<String>,
+    pub items: Vec<SharedListItem>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct SharedListItem {
+    pub manga: Arc<Manga>,
+    pub created_at: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    /// Opaque cursor for the next page, `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct MangaRevision {
+    pub id: i64,
+    pub manga_id: i64,
+    /// User whose sync caused the change, only shown to admins and to that user.
+    pub user_id: Option<i64>,
+    /// Changed fields as `{"field": {"from": old, "to": new}}`.
+    pub changes: serde_json::Value,
+    pub created_at: i64,
+}
+
+/// Manga of different sources the user confirmed as the same series.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct MangaLinkGroup {
+    pub id: i64,
+    pub manga: Vec<Arc<Manga>>,
+    pub created_at: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct LinkSuggestion {
+    pub manga: Vec<Arc<Manga>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Recommendation {
+    pub manga: Arc<Manga>,
+    /// Share of the user's tag profile the manga matches, 0 to 1.
+    pub tag_score: f64,
+    /// Weighted share of similar users who favourited the manga, 0 to 1.
+    pub collaborative_score: f64,
+    pub score: f64,
+}
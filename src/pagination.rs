@@ -0,0 +1,103 @@
+use std::borrow::Cow;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_aux::field_attributes::deserialize_option_number_from_string;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::{error::Error, model::Page};
+
+pub const DEFAULT_LIMIT: i64 = 20;
+pub const MAX_LIMIT: i64 = 100;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Validate)]
+pub struct Pagination {
+    /// `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    #[validate(range(min = 1, max = MAX_LIMIT))]
+    pub limit: Option<i64>,
+}
+
+impl Pagination {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT)
+    }
+
+    /// Decodes the cursor into the position type of the listing, `None` for the first page.
+    pub fn position<T: DeserializeOwned>(&self) -> Result<Option<T>, Error> {
+        self.cursor.as_deref().map(decode_cursor).transpose()
+    }
+}
+
+pub fn encode_cursor<T: Serialize>(position: &T) -> String {
+    // Serializing plain position structs cannot fail.
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(position).unwrap_or_default())
+}
+
+pub fn decode_cursor<T: DeserializeOwned>(cursor: &str) -> Result<T, Error> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(invalid_cursor)
+}
+
+/// Error for a cursor that was not handed out by the listing, or was tampered with.
+pub fn invalid_cursor() -> Error {
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        "cursor",
+        ValidationError::new("cursor_invalid").with_message(Cow::from("Invalid cursor")),
+    );
+
+    Error::Validation(errors)
+}
+
+/// Builds a page from up to `limit + 1` rows, the extra row only signals that a next page
+/// exists. `position` returns where the next page starts after the given last row.
+pub fn paginate<T, P: Serialize>(
+    mut rows: Vec<T>,
+    limit: i64,
+    position: impl FnOnce(&T) -> P,
+) -> Page<T> {
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit.max(0) as usize);
+
+    let next_cursor = match rows.last() {
+        Some(last) if has_more => Some(encode_cursor(&position(last))),
+        _ => None,
+    };
+
+    Page {
+        data: rows,
+        next_cursor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_cursor, encode_cursor, paginate};
+
+    #[test]
+    fn cursor_should_round_trip() {
+        let cursor = encode_cursor(&(42_i64, "key".to_string()));
+
+        let position: (i64, String) = decode_cursor(&cursor).unwrap();
+        assert_eq!(position, (42, "key".to_string()));
+
+        assert!(decode_cursor::<(i64, String)>("not a cursor").is_err());
+    }
+
+    #[test]
+    fn paginate_should_only_set_cursor_when_more_rows_exist() {
+        let page = paginate(vec![1, 2, 3], 2, |last| *last);
+        assert_eq!(page.data, vec![1, 2]);
+        assert_eq!(decode_cursor::<i32>(&page.next_cursor.unwrap()).unwrap(), 2);
+
+        let page = paginate(vec![1, 2], 2, |last| *last);
+        assert_eq!(page.data, vec![1, 2]);
+        assert!(page.next_cursor.is_none());
+    }
+}
@@ -7,7 +7,7 @@ use axum::{
     http::{HeaderName, Request, header},
     middleware,
     response::Response,
-    routing::{get, post},
+    routing::{delete, get, patch, post, put},
 };
 use tower::ServiceBuilder;
 use tower_http::{
@@ -20,7 +20,10 @@ use tracing::{
     field::{self, display},
 };
 
-use crate::{middlewares::jwt_auth_middleware, state::AppState};
+use crate::{
+    middlewares::{admin_middleware, catalog_auth_middleware, jwt_auth_middleware},
+    state::AppState,
+};
 
 const REQUEST_ID_HEADER: &str = "x-request-id";
 
@@ -33,7 +36,35 @@ pub fn init_router(app_state: AppState) -> Router {
 
     let manga_route = Router::new()
         .route("/", get(crate::controllers::manga::index))
-        .route("/{id}", get(crate::controllers::manga::show));
+        .route("/search", get(crate::controllers::manga::search))
+        .route("/{id}", get(crate::controllers::manga::show))
+        .route("/{id}/revisions", get(crate::controllers::manga::revisions))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            catalog_auth_middleware,
+        ));
+
+    let covers_route = Router::new()
+        .route("/{manga_id}", get(crate::controllers::covers::show))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            catalog_auth_middleware,
+        ));
+
+    let tags_route = Router::new()
+        .route("/", get(crate::controllers::tags::index))
+        .route("/{id}/manga", get(crate::controllers::tags::manga))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            catalog_auth_middleware,
+        ));
+
+    let sources_route = Router::new()
+        .route("/", get(crate::controllers::sources::index))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            catalog_auth_middleware,
+        ));
 
     let resources_favourites_route = Router::new()
         .route("/", post(crate::controllers::resources::favourites::store))
@@ -53,8 +84,118 @@ pub fn init_router(app_state: AppState) -> Router {
             jwt_auth_middleware,
         ));
 
+    let resources_bookmarks_route = Router::new()
+        .route("/", post(crate::controllers::resources::bookmarks::store))
+        .layer(DefaultBodyLimit::max(52_428_800)) // 50MB in binary bytes. https://www.gbmb.org/mb-to-bytes
+        .route("/", get(crate::controllers::resources::bookmarks::index))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            jwt_auth_middleware,
+        ));
+
+    let resources_sources_route = Router::new()
+        .route("/", post(crate::controllers::resources::sources::store))
+        .route("/", get(crate::controllers::resources::sources::index))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            jwt_auth_middleware,
+        ));
+
+    let resources_settings_route = Router::new()
+        .route("/", post(crate::controllers::resources::settings::store))
+        .route("/", get(crate::controllers::resources::settings::index))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            jwt_auth_middleware,
+        ));
+
+    let resources_tracking_route = Router::new()
+        .route("/", post(crate::controllers::resources::tracking::store))
+        .layer(DefaultBodyLimit::max(52_428_800)) // 50MB in binary bytes. https://www.gbmb.org/mb-to-bytes
+        .route("/", get(crate::controllers::resources::tracking::index))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            jwt_auth_middleware,
+        ));
+
+    let resources_chapters_route = Router::new()
+        .route("/", post(crate::controllers::resources::chapters::store))
+        .layer(DefaultBodyLimit::max(52_428_800)) // 50MB in binary bytes. https://www.gbmb.org/mb-to-bytes
+        .route("/", get(crate::controllers::resources::chapters::index))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            jwt_auth_middleware,
+        ));
+
+    let lists_route = Router::new()
+        .route("/{slug}", get(crate::controllers::lists::show))
+        .route("/{slug}/feed", get(crate::controllers::lists::feed));
+
     let me_route = Router::new()
-        .route("/", get(crate::controllers::me::index))
+        .route(
+            "/",
+            get(crate::controllers::me::index)
+                .patch(crate::controllers::me::update)
+                .delete(crate::controllers::me::destroy),
+        )
+        .route(
+            "/continue",
+            get(crate::controllers::continue_reading::index),
+        )
+        .route("/export", get(crate::controllers::export::index))
+        .route(
+            "/links",
+            get(crate::controllers::links::index).post(crate::controllers::links::store),
+        )
+        .route(
+            "/links/suggestions",
+            get(crate::controllers::links::suggestions),
+        )
+        .route(
+            "/links/{group_id}",
+            delete(crate::controllers::links::destroy),
+        )
+        .route("/lists", get(crate::controllers::lists::index))
+        .route(
+            "/lists/{category_id}",
+            put(crate::controllers::lists::update).delete(crate::controllers::lists::destroy),
+        )
+        .route(
+            "/recommendations",
+            get(crate::controllers::recommendations::index),
+        )
+        .route("/stats", get(crate::controllers::stats::index))
+        .route("/stats/weekly", get(crate::controllers::stats::weekly))
+        .route("/stats/tags", get(crate::controllers::stats::tags))
+        .route("/stats/sources", get(crate::controllers::stats::sources))
+        .route("/timeline", get(crate::controllers::timeline::index))
+        .route(
+            "/import",
+            post(crate::controllers::import::store).layer(DefaultBodyLimit::max(52_428_800)), // 50MB in binary bytes. https://www.gbmb.org/mb-to-bytes
+        )
+        .route(
+            "/import/kotatsu-backup",
+            post(crate::controllers::import::store_kotatsu)
+                .layer(DefaultBodyLimit::max(52_428_800)), // 50MB in binary bytes. https://www.gbmb.org/mb-to-bytes
+        )
+        .route(
+            "/import/mihon-backup",
+            post(crate::controllers::import::store_mihon).layer(DefaultBodyLimit::max(52_428_800)), // 50MB in binary bytes. https://www.gbmb.org/mb-to-bytes
+        )
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            jwt_auth_middleware,
+        ));
+
+    let admin_route = Router::new()
+        .route("/users", get(crate::controllers::admin::users::index))
+        .route(
+            "/users/{id}",
+            patch(crate::controllers::admin::users::update)
+                .get(crate::controllers::admin::users::show)
+                .delete(crate::controllers::admin::users::destroy),
+        )
+        .layer(middleware::from_fn(admin_middleware))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             jwt_auth_middleware,
@@ -104,10 +245,20 @@ pub fn init_router(app_state: AppState) -> Router {
         )
         .layer(PropagateRequestIdLayer::new(x_request_id_header));
 
-    app.nest("/manga", manga_route)
+    app.nest("/admin", admin_route)
+        .nest("/covers", covers_route)
+        .nest("/lists", lists_route)
+        .nest("/manga", manga_route)
         .nest("/me", me_route)
+        .nest("/resource/bookmarks", resources_bookmarks_route)
+        .nest("/resource/chapters", resources_chapters_route)
         .nest("/resource/favourites", resources_favourites_route)
         .nest("/resource/history", resources_history_route)
+        .nest("/resource/settings", resources_settings_route)
+        .nest("/resource/sources", resources_sources_route)
+        .nest("/resource/tracking", resources_tracking_route)
+        .nest("/sources", sources_route)
+        .nest("/tags", tags_route)
         .layer(CompressionLayer::new())
         .layer(request_id_middleware)
         .with_state(state)
@@ -2,18 +2,21 @@ use std::sync::Arc;
 
 use sqlx::{PgPool, postgres::PgPoolOptions};
 
-use crate::config::Config;
+use crate::{cache::UserCache, config::Config, covers::CoverCache};
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: PgPool,
     pub config: Config,
+    pub stats_cache: UserCache,
+    /// `None` unless `covers.enabled` is set.
+    pub covers: Option<CoverCache>,
 }
 
 pub type SharedAppState = Arc<AppState>;
 
 impl AppState {
-    pub async fn init(config: Config) -> Result<Self, sqlx::Error> {
+    pub async fn init(config: Config) -> Result<Self, anyhow::Error> {
         let pool = PgPoolOptions::new()
             .min_connections(5)
             .max_connections(30)
@@ -24,6 +27,16 @@ impl AppState {
             sqlx::migrate!("./migrations").run(&pool).await?;
         }
 
-        Ok(AppState { pool, config })
+        let covers = match config.covers.enabled {
+            true => Some(CoverCache::new(&config.covers)?),
+            false => None,
+        };
+
+        Ok(AppState {
+            pool,
+            config,
+            stats_cache: UserCache::default(),
+            covers,
+        })
     }
 }
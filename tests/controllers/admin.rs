@@ -0,0 +1,147 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::{db::user::create_user, model::UserSummary};
+use serde_json::json;
+
+use crate::AppStateTest;
+
+#[tokio::test]
+async fn index_should_be_ok_with_search() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+    test_state.generate_jwt_with_email("first@localhost").await;
+    test_state.generate_jwt_with_email("second@localhost").await;
+
+    // -----------------------------------------------------------------------------
+    let request = Request::builder()
+        .uri("/admin/users")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let users: Vec<UserSummary> = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(users.len(), 3);
+    assert_eq!(users[1].favourites_count, 0);
+    assert_eq!(users[1].history_count, 0);
+
+    // -----------------------------------------------------------------------------
+    let request = Request::builder()
+        .uri("/admin/users?search=localhost&limit=1")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let users: Vec<UserSummary> = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].email, "first@localhost");
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn update_should_disable_user() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+    let (user, user_token) = test_state.generate_jwt_with_email("member@localhost").await;
+
+    let request = Request::builder()
+        .method("PATCH")
+        .uri(format!("/admin/users/{}", user.id))
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "is_disabled": true })).unwrap(),
+        ))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let summary: UserSummary = serde_json::from_slice(&response_body).unwrap();
+    assert!(summary.is_disabled);
+
+    let request = Request::builder()
+        .uri("/me")
+        .header(
+            http::header::AUTHORIZATION,
+            format!("bearer {}", user_token),
+        )
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn update_should_be_error_when_target_is_self() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (admin, token) = test_state.generate_jwt_with_user().await;
+
+    let request = Request::builder()
+        .method("PATCH")
+        .uri(format!("/admin/users/{}", admin.id))
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "is_admin": false })).unwrap(),
+        ))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn destroy_should_delete_user() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+    let (user, _) = test_state.generate_jwt_with_email("member@localhost").await;
+
+    let request = Request::builder()
+        .method("DELETE")
+        .uri(format!("/admin/users/{}", user.id))
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let request = Request::builder()
+        .uri(format!("/admin/users/{}", user.id))
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn concurrent_first_registrations_should_make_one_admin() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let registrations = (0..4)
+        .map(|index| create_user(pool, format!("user{}@localhost", index), "password".into()));
+    let users = futures::future::try_join_all(registrations).await.unwrap();
+    assert_eq!(users.iter().filter(|(user, _)| user.is_admin).count(), 1);
+
+    test_state.cleanup().await;
+}
@@ -0,0 +1,86 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::model::UserBookmark;
+use serde_json::json;
+
+use crate::AppStateTest;
+
+#[tokio::test]
+async fn should_be_error_when_accessed_without_auth() {
+    let test_state = AppStateTest::new(false).await;
+
+    let request = Request::builder()
+        .uri("/resource/bookmarks")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn store_should_sync_bookmarks() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let body = json!({
+        "bookmarks": [{
+            "manga_id": 10,
+            "manga": {
+                "manga_id": 10,
+                "title": "Title",
+                "alt_title": null,
+                "url": "/manga/10",
+                "public_url": "https://localhost/manga/10",
+                "rating": 0.5,
+                "nsfw": 0,
+                "content_rating": null,
+                "cover_url": "https://localhost/cover.jpg",
+                "large_cover_url": null,
+                "state": null,
+                "author": null,
+                "source": "SOURCE",
+                "tags": [{ "tag_id": 1, "title": "Action", "key": "action", "source": "SOURCE" }]
+            },
+            "page_id": 5,
+            "chapter_id": 3,
+            "page": 4,
+            "scroll": 0,
+            "image_url": "https://localhost/4.jpg",
+            "created_at": 1,
+            "percent": 0.5,
+            "deleted_at": 0
+        }],
+        "timestamp": 1
+    });
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/resource/bookmarks")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .uri("/resource/bookmarks")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let user_bookmark: UserBookmark = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(user_bookmark.bookmarks.len(), 1);
+    assert_eq!(user_bookmark.bookmarks[0].page_id, 5);
+    assert_eq!(user_bookmark.bookmarks[0].manga.tags.len(), 1);
+
+    test_state.cleanup().await;
+}
@@ -0,0 +1,108 @@
+use axum::{
+    body::Body,
+    http::{self, Request, Response, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::{
+    config::Config,
+    model::{Manga, Page, SourceStat, TagStat},
+};
+use serde::de::DeserializeOwned;
+
+use crate::{AppStateTest, insert_fake_history, insert_fake_manga};
+
+async fn get(test_state: &AppStateTest, uri: &str, token: Option<&str>) -> Response<Body> {
+    let mut request = Request::builder().uri(uri);
+    if let Some(token) = token {
+        request = request.header(http::header::AUTHORIZATION, format!("bearer {}", token));
+    }
+
+    test_state
+        .generate_response(request.body(Body::empty()).unwrap())
+        .await
+}
+
+async fn get_page<T: DeserializeOwned>(
+    test_state: &AppStateTest,
+    uri: &str,
+    token: Option<&str>,
+) -> Page<T> {
+    let response = get(test_state, uri, token).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&response_body).unwrap()
+}
+
+#[tokio::test]
+async fn should_be_error_when_accessed_without_auth() {
+    let test_state = AppStateTest::new(false).await;
+
+    for uri in ["/tags", "/sources", "/tags/1/manga"] {
+        let response = get(&test_state, uri, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[tokio::test]
+async fn should_browse_tags_and_sources_of_own_library() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let (other, _) = test_state.generate_jwt_with_email("other@email.com").await;
+    for _ in 0..3 {
+        let manga_id = insert_fake_manga(pool, Some(2)).await;
+        insert_fake_history(pool, user.id, manga_id).await;
+    }
+    let other_manga_id = insert_fake_manga(pool, Some(2)).await;
+    insert_fake_history(pool, other.id, other_manga_id).await;
+
+    let mut tags: Vec<TagStat> = Vec::new();
+    let mut uri = "/tags?limit=4".to_string();
+    loop {
+        let page: Page<TagStat> = get_page(&test_state, &uri, Some(&token)).await;
+        tags.extend(page.data);
+        match page.next_cursor {
+            Some(cursor) => uri = format!("/tags?limit=4&cursor={}", cursor),
+            None => break,
+        }
+    }
+    assert_eq!(tags.len(), 6);
+    assert!(tags.iter().all(|tag| tag.count == 1));
+
+    let page: Page<TagStat> = get_page(&test_state, "/tags?source=unknown", Some(&token)).await;
+    assert!(page.data.is_empty());
+
+    let sources: Page<SourceStat> = get_page(&test_state, "/sources", Some(&token)).await;
+    assert_eq!(sources.data.len(), 1);
+    assert_eq!(sources.data[0].count, 3);
+
+    let uri = format!("/tags/{}/manga", tags[0].tag.tag_id);
+    let mangas: Page<Manga> = get_page(&test_state, &uri, Some(&token)).await;
+    assert_eq!(mangas.data.len(), 1);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn should_browse_whole_catalog_anonymously_in_global_catalog_mode() {
+    let mut config = Config::new().unwrap();
+    config.application.global_catalog = true;
+    let mut test_state = AppStateTest::new_with_config(true, config).await;
+
+    insert_fake_manga(&test_state.app_state.pool, Some(1)).await;
+    insert_fake_manga(&test_state.app_state.pool, Some(1)).await;
+
+    let sources: Page<SourceStat> = get_page(&test_state, "/sources", None).await;
+    assert_eq!(sources.data[0].count, 2);
+
+    let tags: Page<TagStat> = get_page(&test_state, "/tags", None).await;
+    assert_eq!(tags.data.len(), 2);
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+    let tags: Page<TagStat> = get_page(&test_state, "/tags", Some(&token)).await;
+    assert!(tags.data.is_empty());
+
+    test_state.cleanup().await;
+}
@@ -0,0 +1,125 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::model::UserChapterRead;
+use serde_json::{Value, json};
+
+use crate::{AppStateTest, insert_fake_history, insert_fake_manga};
+
+async fn store(test_state: &AppStateTest, token: &str, chapters: Value) -> StatusCode {
+    let body = json!({ "chapters": chapters, "timestamp": 1 });
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/resource/chapters")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+
+    test_state.generate_response(request).await.status()
+}
+
+async fn index(test_state: &AppStateTest, token: &str) -> UserChapterRead {
+    let request = Request::builder()
+        .uri("/resource/chapters")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&response_body).unwrap()
+}
+
+#[tokio::test]
+async fn should_be_error_when_accessed_without_auth() {
+    let test_state = AppStateTest::new(false).await;
+
+    let request = Request::builder()
+        .uri("/resource/chapters")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn store_should_merge_chapter_reads() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let status = store(
+        &test_state,
+        &token,
+        json!([
+            { "manga_id": 10, "chapter_id": 1, "read_at": 100, "percent": 1.0, "deleted_at": 0 },
+            { "manga_id": 10, "chapter_id": 2, "read_at": 200, "percent": 0.4, "deleted_at": 0 }
+        ]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let status = store(
+        &test_state,
+        &token,
+        json!([
+            { "manga_id": 10, "chapter_id": 1, "read_at": 50, "percent": 0.2, "deleted_at": 300 },
+            { "manga_id": 10, "chapter_id": 2, "read_at": 150, "percent": 0.8, "deleted_at": 0 }
+        ]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let mut user_chapter = index(&test_state, &token).await;
+    user_chapter.chapters.sort_by_key(|c| c.chapter_id);
+    assert_eq!(user_chapter.chapters.len(), 2);
+    assert_eq!(user_chapter.chapters[0].read_at, 100);
+    assert_eq!(user_chapter.chapters[0].percent, 1.0);
+    assert_eq!(user_chapter.chapters[0].deleted_at, 300);
+    assert_eq!(user_chapter.chapters[1].read_at, 200);
+    assert_eq!(user_chapter.chapters[1].percent, 0.8);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn store_should_derive_history_position() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let manga_id = insert_fake_manga(&test_state.app_state.pool, None).await;
+    insert_fake_history(&test_state.app_state.pool, user.id, manga_id).await;
+
+    let status = store(
+        &test_state,
+        &token,
+        json!([
+            { "manga_id": manga_id, "chapter_id": 6, "read_at": 400, "percent": 1.0, "deleted_at": 0 },
+            { "manga_id": manga_id, "chapter_id": 7, "read_at": 500, "percent": 0.3, "deleted_at": 0 },
+            { "manga_id": manga_id, "chapter_id": 8, "read_at": 600, "percent": 0.1, "deleted_at": 700 }
+        ]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let history = sqlx::query!(
+        "SELECT chapter_id, updated_at, percent FROM history WHERE user_id = $1 AND manga_id = $2",
+        user.id,
+        manga_id
+    )
+    .fetch_one(&test_state.app_state.pool)
+    .await
+    .unwrap();
+    assert_eq!(history.chapter_id, 7);
+    assert_eq!(history.updated_at, 500);
+    // One of the 10 chapters read before, and 30% into this one.
+    assert!((history.percent - 0.13).abs() < 1e-6);
+
+    test_state.cleanup().await;
+}
@@ -0,0 +1,94 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::model::History;
+
+use crate::{AppStateTest, insert_fake_history, insert_fake_manga};
+
+async fn continue_reading(test_state: &AppStateTest, token: &str, uri: &str) -> Vec<History> {
+    let request = Request::builder()
+        .uri(uri)
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&response_body).unwrap()
+}
+
+#[tokio::test]
+async fn should_return_recent_unfinished_history() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let mut manga_ids = Vec::new();
+    for _ in 0..5 {
+        let manga_id = insert_fake_manga(pool, None).await;
+        insert_fake_history(pool, user.id, manga_id).await;
+        manga_ids.push(manga_id);
+    }
+
+    for (updated_at, manga_id) in manga_ids.iter().enumerate() {
+        sqlx::query!(
+            "UPDATE history SET updated_at = $1 WHERE manga_id = $2",
+            updated_at as i64 + 10,
+            manga_id
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+    sqlx::query!(
+        "UPDATE history SET percent = 1 WHERE manga_id = $1",
+        manga_ids[3]
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE history SET deleted_at = 5 WHERE manga_id = $1",
+        manga_ids[2]
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    // Midway through the last of its 10 chapters, still something to continue.
+    sqlx::query!(
+        "UPDATE history SET percent = 0.95 WHERE manga_id = $1",
+        manga_ids[4]
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let history = continue_reading(&test_state, &token, "/me/continue").await;
+    let ids: Vec<i64> = history.iter().map(|h| h.manga_id).collect();
+    assert_eq!(ids, vec![manga_ids[4], manga_ids[1], manga_ids[0]]);
+
+    let history = continue_reading(&test_state, &token, "/me/continue?limit=1").await;
+    assert_eq!(history.len(), 1);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn should_be_error_when_limit_is_too_large() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let request = Request::builder()
+        .uri("/me/continue?limit=51")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    test_state.cleanup().await;
+}
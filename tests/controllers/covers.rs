@@ -0,0 +1,301 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use axum::{
+    Router,
+    body::Body,
+    http::{self, Request, Response, StatusCode, header},
+    routing::get,
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::config::{Config, CoverStorage};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+use crate::{AppStateTest, insert_fake_manga};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\nfake-png-data";
+
+/// Serves fake covers on a random local port, counting the requests for `/cover.png`.
+async fn spawn_source_site() -> (SocketAddr, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+
+    let router = Router::new()
+        .route(
+            "/cover.png",
+            get(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { ([(header::CONTENT_TYPE, "image/png")], PNG) }
+            }),
+        )
+        .route(
+            "/page.html",
+            get(|| async { ([(header::CONTENT_TYPE, "text/html")], "<html></html>") }),
+        )
+        .route(
+            "/big.png",
+            get(|| async {
+                let mut body = PNG.to_vec();
+                body.resize(4096, 0);
+                ([(header::CONTENT_TYPE, "image/png")], body)
+            }),
+        );
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    (address, hits)
+}
+
+fn covers_config(path: &str) -> Config {
+    let mut config = Config::new().unwrap();
+    config.application.global_catalog = true;
+    config.covers.enabled = true;
+    config.covers.max_size = 1024;
+    config.covers.storage = CoverStorage::Local {
+        path: path.to_string(),
+    };
+    // The fake source site listens on loopback.
+    config.covers.allow_private_addresses = true;
+
+    config
+}
+
+fn covers_path() -> String {
+    std::env::temp_dir()
+        .join(format!("rustatsu_covers_{}", Uuid::new_v4().simple()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+async fn insert_manga_with_cover(test_state: &AppStateTest, cover_url: &str) -> i64 {
+    let pool = &test_state.app_state.pool;
+    let manga_id = insert_fake_manga(pool, None).await;
+
+    sqlx::query("UPDATE mangas SET cover_url = $1 WHERE id = $2")
+        .bind(cover_url)
+        .bind(manga_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    manga_id
+}
+
+async fn get_cover(test_state: &AppStateTest, manga_id: i64, etag: Option<&str>) -> Response<Body> {
+    get_cover_uri(test_state, &format!("/covers/{}", manga_id), etag).await
+}
+
+async fn get_cover_uri(test_state: &AppStateTest, uri: &str, etag: Option<&str>) -> Response<Body> {
+    let mut request = Request::builder().uri(uri);
+    if let Some(etag) = etag {
+        request = request.header(http::header::IF_NONE_MATCH, etag);
+    }
+
+    test_state
+        .generate_response(request.body(Body::empty()).unwrap())
+        .await
+}
+
+#[tokio::test]
+async fn show_should_be_not_found_when_covers_disabled() {
+    let mut config = Config::new().unwrap();
+    config.application.global_catalog = true;
+    let mut test_state = AppStateTest::new_with_config(true, config).await;
+
+    let manga_id = insert_fake_manga(&test_state.app_state.pool, None).await;
+
+    let response = get_cover(&test_state, manga_id, None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn show_should_fetch_once_and_serve_from_cache() {
+    let path = covers_path();
+    let mut test_state = AppStateTest::new_with_config(true, covers_config(&path)).await;
+    let (address, hits) = spawn_source_site().await;
+
+    let manga_id =
+        insert_manga_with_cover(&test_state, &format!("http://{}/cover.png", address)).await;
+
+    let response = get_cover(&test_state, manga_id, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+    let etag = response.headers()[header::ETAG]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&response_body[..], PNG);
+
+    let response = get_cover(&test_state, manga_id, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::ETAG], etag.as_str());
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&response_body[..], PNG);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    let response = get_cover(&test_state, manga_id, Some(&etag)).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // Revalidating only looks at the stored metadata: with the stored bytes corrupted in
+    // place, keeping size and modification time, the ETag still matches.
+    let file = std::path::Path::new(&path)
+        .join(manga_id.to_string())
+        .join("cover")
+        .read_dir()
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let modified = std::fs::metadata(&file).unwrap().modified().unwrap();
+    let mut stored = std::fs::OpenOptions::new().write(true).open(&file).unwrap();
+    std::io::Write::write_all(&mut stored, &vec![0; PNG.len()]).unwrap();
+    stored.set_modified(modified).unwrap();
+    drop(stored);
+
+    let response = get_cover(&test_state, manga_id, Some(&etag)).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    let response = get_cover(&test_state, manga_id, None).await;
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+    let response = get_cover(&test_state, manga_id + 1, None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_state.cleanup().await;
+    let _ = std::fs::remove_dir_all(path);
+}
+
+#[tokio::test]
+async fn show_should_cache_both_cover_sizes() {
+    let path = covers_path();
+    let mut test_state = AppStateTest::new_with_config(true, covers_config(&path)).await;
+    let (address, hits) = spawn_source_site().await;
+
+    let manga_id =
+        insert_manga_with_cover(&test_state, &format!("http://{}/cover.png", address)).await;
+    sqlx::query("UPDATE mangas SET large_cover_url = $1 WHERE id = $2")
+        .bind(format!("http://{}/cover.png?size=large", address))
+        .bind(manga_id)
+        .execute(&test_state.app_state.pool)
+        .await
+        .unwrap();
+
+    for _ in 0..2 {
+        for uri in [
+            format!("/covers/{}", manga_id),
+            format!("/covers/{}?large=true", manga_id),
+        ] {
+            let response = get_cover_uri(&test_state, &uri, None).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    test_state.cleanup().await;
+    let _ = std::fs::remove_dir_all(path);
+}
+
+#[tokio::test]
+async fn show_should_reject_invalid_covers() {
+    let path = covers_path();
+    let mut test_state = AppStateTest::new_with_config(true, covers_config(&path)).await;
+    let (address, _) = spawn_source_site().await;
+
+    let html_manga_id =
+        insert_manga_with_cover(&test_state, &format!("http://{}/page.html", address)).await;
+    let big_manga_id =
+        insert_manga_with_cover(&test_state, &format!("http://{}/big.png", address)).await;
+    let missing_manga_id =
+        insert_manga_with_cover(&test_state, &format!("http://{}/missing.png", address)).await;
+
+    for manga_id in [html_manga_id, big_manga_id, missing_manga_id] {
+        let response = get_cover(&test_state, manga_id, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    test_state.cleanup().await;
+    let _ = std::fs::remove_dir_all(path);
+}
+
+#[tokio::test]
+async fn show_should_reject_private_addresses() {
+    let path = covers_path();
+    let mut config = covers_config(&path);
+    config.covers.allow_private_addresses = false;
+    let mut test_state = AppStateTest::new_with_config(true, config).await;
+    let (address, hits) = spawn_source_site().await;
+
+    for cover_url in [
+        format!("http://{}/cover.png", address),
+        format!("http://localhost:{}/cover.png", address.port()),
+        format!("http://[::ffff:127.0.0.1]:{}/cover.png", address.port()),
+    ] {
+        let manga_id = insert_manga_with_cover(&test_state, &cover_url).await;
+
+        let response = get_cover(&test_state, manga_id, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let response_body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(response_body.is_empty());
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+    test_state.cleanup().await;
+    let _ = std::fs::remove_dir_all(path);
+}
+
+#[tokio::test]
+async fn cleanup_should_delete_covers_of_orphaned_manga() {
+    let path = covers_path();
+    let mut test_state = AppStateTest::new_with_config(true, covers_config(&path)).await;
+    let (address, _) = spawn_source_site().await;
+    let cover_url = format!("http://{}/cover.png", address);
+
+    let kept_manga_id = insert_manga_with_cover(&test_state, &cover_url).await;
+    let orphaned_manga_id = insert_manga_with_cover(&test_state, &cover_url).await;
+    for manga_id in [kept_manga_id, orphaned_manga_id] {
+        let response = get_cover(&test_state, manga_id, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    sqlx::query("DELETE FROM mangas WHERE id = $1")
+        .bind(orphaned_manga_id)
+        .execute(&test_state.app_state.pool)
+        .await
+        .unwrap();
+
+    let covers = test_state.app_state.covers.as_ref().unwrap();
+    let deleted = covers.cleanup(&test_state.app_state.pool).await.unwrap();
+    assert_eq!(deleted, 1);
+
+    let directory = std::path::Path::new(&path);
+    assert!(
+        directory
+            .join(kept_manga_id.to_string())
+            .join("cover")
+            .read_dir()
+            .unwrap()
+            .next()
+            .is_some()
+    );
+    assert!(
+        !directory
+            .join(orphaned_manga_id.to_string())
+            .join("cover")
+            .read_dir()
+            .is_ok_and(|mut files| files.next().is_some())
+    );
+
+    test_state.cleanup().await;
+    let _ = std::fs::remove_dir_all(path);
+}
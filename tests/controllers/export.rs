@@ -0,0 +1,96 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::backup::ImportSummary;
+
+use crate::{AppStateTest, insert_fake_favourite, insert_fake_history, insert_fake_manga};
+
+#[tokio::test]
+async fn export_should_be_error_when_accessed_without_auth() {
+    let test_state = AppStateTest::new(false).await;
+
+    let request = Request::builder()
+        .uri("/me/export")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn export_should_be_importable() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let (other_user, other_token) = test_state.generate_jwt_with_email("other@localhost").await;
+
+    let manga_id = insert_fake_manga(pool, None).await;
+    insert_fake_favourite(pool, user.id, manga_id).await;
+    insert_fake_history(pool, user.id, manga_id).await;
+
+    let request = Request::builder()
+        .uri("/me/export")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(http::header::CONTENT_TYPE).unwrap(),
+        "application/zip"
+    );
+
+    let archive = response.into_body().collect().await.unwrap().to_bytes();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/me/import")
+        .header(
+            http::header::AUTHORIZATION,
+            format!("bearer {}", other_token),
+        )
+        .header(http::header::CONTENT_TYPE, "application/zip")
+        .body(Body::from(archive))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let summary: ImportSummary = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(summary.categories, 1);
+    assert_eq!(summary.favourites, 1);
+    assert_eq!(summary.history, 1);
+
+    let result = sqlx::query!(
+        r#"SELECT count(*) FROM favourites WHERE user_id = $1"#,
+        other_user.id
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert_eq!(result.count, Some(1));
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn import_should_be_error_when_archive_is_invalid() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/me/import")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::from("not a zip"))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    test_state.cleanup().await;
+}
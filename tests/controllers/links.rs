@@ -0,0 +1,203 @@
+use axum::{
+    body::Body,
+    http::{self, Request, Response, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::model::{History, LinkSuggestion, MangaLinkGroup, ReadingSummary};
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::{AppStateTest, insert_fake_history, insert_fake_manga};
+
+async fn send(
+    test_state: &AppStateTest,
+    method: &str,
+    uri: &str,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> Response<Body> {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json");
+    let body = match body {
+        Some(body) => Body::from(serde_json::to_vec(&body).unwrap()),
+        None => Body::empty(),
+    };
+
+    test_state
+        .generate_response(request.body(body).unwrap())
+        .await
+}
+
+async fn get<T: serde::de::DeserializeOwned>(
+    test_state: &AppStateTest,
+    uri: &str,
+    token: &str,
+) -> T {
+    let response = send(test_state, "GET", uri, token, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&response_body).unwrap()
+}
+
+/// Inserts a manga in the user's history under the given title, source and author.
+async fn insert_read_manga(
+    pool: &PgPool,
+    user_id: i64,
+    title: &str,
+    source: &str,
+    author: Option<&str>,
+    updated_at: i64,
+) -> i64 {
+    let manga_id = insert_fake_manga(pool, None).await;
+    insert_fake_history(pool, user_id, manga_id).await;
+
+    sqlx::query!(
+        "UPDATE mangas SET title = $1, source = $2, author = $3 WHERE id = $4",
+        title,
+        source,
+        author,
+        manga_id
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE history SET updated_at = $1 WHERE manga_id = $2",
+        updated_at,
+        manga_id
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    manga_id
+}
+
+#[tokio::test]
+async fn should_suggest_and_link_manga_across_sources() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let first = insert_read_manga(pool, user.id, "Berserk", "A", Some("Miura Kentaro"), 10).await;
+    let second = insert_read_manga(pool, user.id, "BERSERK!", "B", Some("Kentaro Miura"), 20).await;
+    insert_read_manga(pool, user.id, "Berserk", "C", Some("Somebody Else"), 30).await;
+    insert_read_manga(pool, user.id, "Vagabond", "A", None, 40).await;
+
+    let suggestions: Vec<LinkSuggestion> = get(&test_state, "/me/links/suggestions", &token).await;
+    assert_eq!(suggestions.len(), 1);
+    let suggested: Vec<i64> = suggestions[0].manga.iter().map(|m| m.manga_id).collect();
+    let mut expected = vec![first, second];
+    expected.sort_unstable();
+    assert_eq!(suggested, expected);
+
+    let summary: ReadingSummary = get(&test_state, "/me/stats", &token).await;
+    assert_eq!(summary.started, 4);
+    let history: Vec<History> = get(&test_state, "/me/continue", &token).await;
+    assert_eq!(history.len(), 4);
+
+    let response = send(
+        &test_state,
+        "POST",
+        "/me/links",
+        &token,
+        Some(json!({ "manga_ids": [first, second] })),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let group: MangaLinkGroup = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(group.manga.len(), 2);
+
+    let groups: Vec<MangaLinkGroup> = get(&test_state, "/me/links", &token).await;
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].id, group.id);
+    let suggestions: Vec<LinkSuggestion> = get(&test_state, "/me/links/suggestions", &token).await;
+    assert!(suggestions.is_empty());
+
+    // The linked manga now count as one series.
+    let summary: ReadingSummary = get(&test_state, "/me/stats", &token).await;
+    assert_eq!(summary.started, 3);
+    let history: Vec<History> = get(&test_state, "/me/continue", &token).await;
+    assert_eq!(history.len(), 3);
+    assert!(history.iter().any(|h| h.manga_id == second));
+    assert!(!history.iter().any(|h| h.manga_id == first));
+
+    let uri = format!("/me/links/{}", group.id);
+    let response = send(&test_state, "DELETE", &uri, &token, None).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = send(&test_state, "DELETE", &uri, &token, None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let history: Vec<History> = get(&test_state, "/me/continue", &token).await;
+    assert_eq!(history.len(), 4);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn store_should_merge_existing_groups() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let a = insert_read_manga(pool, user.id, "Berserk", "A", None, 10).await;
+    let b = insert_read_manga(pool, user.id, "Berserk", "B", None, 20).await;
+    let c = insert_read_manga(pool, user.id, "Berserk", "C", None, 30).await;
+
+    for manga_ids in [[a, b], [b, c]] {
+        let response = send(
+            &test_state,
+            "POST",
+            "/me/links",
+            &token,
+            Some(json!({ "manga_ids": manga_ids })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    let groups: Vec<MangaLinkGroup> = get(&test_state, "/me/links", &token).await;
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].manga.len(), 3);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn store_should_be_error_when_request_invalid() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let (_, other_token) = test_state.generate_jwt_with_email("other@email.com").await;
+    let a = insert_read_manga(pool, user.id, "Berserk", "A", None, 10).await;
+    let b = insert_read_manga(pool, user.id, "Berserk", "B", None, 20).await;
+
+    let response = send(
+        &test_state,
+        "POST",
+        "/me/links",
+        &token,
+        Some(json!({ "manga_ids": [a, a] })),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Only manga in the caller's own library can be linked.
+    let response = send(
+        &test_state,
+        "POST",
+        "/me/links",
+        &other_token,
+        Some(json!({ "manga_ids": [a, b] })),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_state.cleanup().await;
+}
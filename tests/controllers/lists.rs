@@ -0,0 +1,173 @@
+use axum::{
+    body::Body,
+    http::{self, Request, Response, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::{
+    config::Config,
+    model::{ContentRating, PublicList, SharedList},
+};
+use serde_json::json;
+
+use crate::{AppStateTest, insert_fake_favourite, insert_fake_manga};
+
+async fn request(
+    test_state: &AppStateTest,
+    method: &str,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> Response<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        builder = builder.header(http::header::AUTHORIZATION, format!("bearer {}", token));
+    }
+    let body = match body {
+        Some(body) => {
+            builder = builder.header(http::header::CONTENT_TYPE, "application/json");
+            Body::from(serde_json::to_vec(&body).unwrap())
+        }
+        None => Body::empty(),
+    };
+
+    test_state
+        .generate_response(builder.body(body).unwrap())
+        .await
+}
+
+async fn body_bytes(response: Response<Body>) -> Vec<u8> {
+    response
+        .into_body()
+        .collect()
+        .await
+        .unwrap()
+        .to_bytes()
+        .to_vec()
+}
+
+#[tokio::test]
+async fn should_publish_and_unpublish_category() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let manga_id = insert_fake_manga(pool, None).await;
+    let nsfw_manga_id = insert_fake_manga(pool, None).await;
+    insert_fake_favourite(pool, user.id, manga_id).await;
+    insert_fake_favourite(pool, user.id, nsfw_manga_id).await;
+    sqlx::query!(
+        "UPDATE mangas SET content_rating = 'ADULT' WHERE id = $1",
+        nsfw_manga_id
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let response = request(&test_state, "PUT", "/me/lists/1", Some(&token), None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let list: PublicList = serde_json::from_slice(&body_bytes(response).await).unwrap();
+    assert_eq!(list.slug.len(), 32);
+    assert!(!list.hide_nsfw);
+
+    let uri = format!("/lists/{}", list.slug);
+    let response = request(&test_state, "GET", &uri, None, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let shared: SharedList = serde_json::from_slice(&body_bytes(response).await).unwrap();
+    assert_eq!(shared.title, "Reading");
+    assert_eq!(shared.items.len(), 2);
+
+    let response = request(
+        &test_state,
+        "PUT",
+        "/me/lists/1",
+        Some(&token),
+        Some(json!({ "hide_nsfw": true })),
+    )
+    .await;
+    let republished: PublicList = serde_json::from_slice(&body_bytes(response).await).unwrap();
+    assert_eq!(republished.slug, list.slug);
+    assert!(republished.hide_nsfw);
+
+    let response = request(&test_state, "GET", &uri, None, None).await;
+    let shared: SharedList = serde_json::from_slice(&body_bytes(response).await).unwrap();
+    assert_eq!(shared.items.len(), 1);
+    assert_eq!(shared.items[0].manga.manga_id, manga_id);
+
+    let response = request(&test_state, "GET", &format!("{}/feed", uri), None, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[http::header::CONTENT_TYPE],
+        "application/atom+xml; charset=utf-8"
+    );
+    let feed = String::from_utf8(body_bytes(response).await).unwrap();
+    assert!(feed.contains(&format!("urn:rustatsu:manga:{}", manga_id)));
+    assert!(feed.contains("<link href="));
+
+    sqlx::query!("UPDATE mangas SET public_url = '' WHERE id = $1", manga_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    let response = request(&test_state, "GET", &format!("{}/feed", uri), None, None).await;
+    let feed = String::from_utf8(body_bytes(response).await).unwrap();
+    assert!(!feed.contains("<link href="));
+
+    let response = request(&test_state, "GET", "/me/lists", Some(&token), None).await;
+    let lists: Vec<PublicList> = serde_json::from_slice(&body_bytes(response).await).unwrap();
+    assert_eq!(lists.len(), 1);
+
+    let response = request(&test_state, "DELETE", "/me/lists/1", Some(&token), None).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = request(&test_state, "GET", &uri, None, None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn shared_list_should_hide_nsfw_when_server_default_hides_it() {
+    let mut config = Config::new().unwrap();
+    config.application.max_content_rating = ContentRating::Suggestive;
+    let mut test_state = AppStateTest::new_with_config(true, config).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let manga_id = insert_fake_manga(pool, None).await;
+    let nsfw_manga_id = insert_fake_manga(pool, None).await;
+    insert_fake_favourite(pool, user.id, manga_id).await;
+    insert_fake_favourite(pool, user.id, nsfw_manga_id).await;
+    sqlx::query!(
+        "UPDATE mangas SET content_rating = 'ADULT' WHERE id = $1",
+        nsfw_manga_id
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let response = request(&test_state, "PUT", "/me/lists/1", Some(&token), None).await;
+    let list: PublicList = serde_json::from_slice(&body_bytes(response).await).unwrap();
+    assert!(!list.hide_nsfw);
+
+    let uri = format!("/lists/{}", list.slug);
+    let response = request(&test_state, "GET", &uri, None, None).await;
+    let shared: SharedList = serde_json::from_slice(&body_bytes(response).await).unwrap();
+    assert_eq!(shared.items.len(), 1);
+    assert_eq!(shared.items[0].manga.manga_id, manga_id);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn should_be_error_when_publishing_unknown_category() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let response = request(&test_state, "PUT", "/me/lists/99", Some(&token), None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = request(&test_state, "DELETE", "/me/lists/99", Some(&token), None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_state.cleanup().await;
+}
@@ -1,24 +1,58 @@
-use axum::{body::Body, http::Request, http::StatusCode};
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{self, Request, Response, StatusCode},
+};
 use http_body_util::BodyExt;
-use rustatsu_sync::model::Manga;
+use rustatsu_sync::{
+    config::{Config, MetadataPolicy},
+    db::manga::{MangaScope, get_manga_by_id, insert_mangas},
+    model::{Manga, MangaRevision, Page},
+    pagination::encode_cursor,
+};
+
+use crate::{AppStateTest, insert_fake_favourite, insert_fake_history, insert_fake_manga};
+
+async fn get(test_state: &AppStateTest, uri: &str, token: Option<&str>) -> Response<Body> {
+    let mut request = Request::builder().uri(uri);
+    if let Some(token) = token {
+        request = request.header(http::header::AUTHORIZATION, format!("bearer {}", token));
+    }
+
+    test_state
+        .generate_response(request.body(Body::empty()).unwrap())
+        .await
+}
+
+#[tokio::test]
+async fn index_should_be_error_when_accessed_without_auth() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let response = get(&test_state, "/manga", None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 
-use crate::{AppStateTest, insert_fake_manga};
+    let response = get(&test_state, "/manga/1", None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    test_state.cleanup().await;
+}
 
 #[tokio::test]
 async fn index_should_be_ok_with_manga_is_empty() {
     let mut test_state = AppStateTest::new(true).await;
 
-    let request = Request::builder()
-        .uri("/manga")
-        .body(Body::empty())
-        .unwrap();
-    let response = test_state.generate_response(request).await;
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let response = get(&test_state, "/manga", Some(&token)).await;
     assert_eq!(response.status(), StatusCode::OK);
 
     let response_body = response.into_body().collect().await.unwrap().to_bytes();
     assert!(!response_body.is_empty());
 
-    let mangas: Vec<Manga> = serde_json::from_slice(&response_body).unwrap();
+    let mangas = serde_json::from_slice::<Page<Manga>>(&response_body)
+        .unwrap()
+        .data;
     assert_eq!(mangas.len(), 0);
 
     test_state.cleanup().await;
@@ -27,19 +61,21 @@ async fn index_should_be_ok_with_manga_is_empty() {
 #[tokio::test]
 async fn index_should_be_ok_with_manga_not_empty() {
     let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
 
-    insert_fake_manga(&test_state.app_state.pool, None).await;
-    insert_fake_manga(&test_state.app_state.pool, None).await;
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let manga_id = insert_fake_manga(pool, None).await;
+    insert_fake_history(pool, user.id, manga_id).await;
+    let manga_id = insert_fake_manga(pool, None).await;
+    insert_fake_favourite(pool, user.id, manga_id).await;
 
-    let request = Request::builder()
-        .uri("/manga")
-        .body(Body::empty())
-        .unwrap();
-    let response = test_state.generate_response(request).await;
+    let response = get(&test_state, "/manga", Some(&token)).await;
     assert_eq!(response.status(), StatusCode::OK);
 
     let response_body = response.into_body().collect().await.unwrap().to_bytes();
-    let mangas: Vec<Manga> = serde_json::from_slice(&response_body).unwrap();
+    let mangas = serde_json::from_slice::<Page<Manga>>(&response_body)
+        .unwrap()
+        .data;
     assert_eq!(mangas.len(), 2);
     assert_eq!(mangas[0].tags.len(), 2);
 
@@ -47,48 +83,179 @@ async fn index_should_be_ok_with_manga_not_empty() {
 }
 
 #[tokio::test]
-async fn index_should_be_ok_when_accessed_with_query() {
+async fn index_should_only_list_manga_from_own_library() {
     let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let (other, _) = test_state.generate_jwt_with_email("other@email.com").await;
+    let manga_id = insert_fake_manga(pool, None).await;
+    insert_fake_history(pool, user.id, manga_id).await;
+    let other_manga_id = insert_fake_manga(pool, None).await;
+    insert_fake_history(pool, other.id, other_manga_id).await;
+
+    let response = get(&test_state, "/manga", Some(&token)).await;
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let mangas = serde_json::from_slice::<Page<Manga>>(&response_body)
+        .unwrap()
+        .data;
+    assert_eq!(mangas.len(), 1);
+    assert_eq!(mangas[0].manga_id, manga_id);
+
+    let response = get(
+        &test_state,
+        &format!("/manga/{}", other_manga_id),
+        Some(&token),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn index_should_list_every_manga_in_global_catalog_mode() {
+    let mut config = Config::new().unwrap();
+    config.application.global_catalog = true;
+    let mut test_state = AppStateTest::new_with_config(true, config).await;
 
-    insert_fake_manga(&test_state.app_state.pool, Some(0)).await;
-    insert_fake_manga(&test_state.app_state.pool, None).await;
     insert_fake_manga(&test_state.app_state.pool, None).await;
+    let manga_id = insert_fake_manga(&test_state.app_state.pool, None).await;
 
-    // -----------------------------------------------------------------------------
-    let request = Request::builder()
-        .uri("/manga?limit=1")
-        .body(Body::empty())
-        .unwrap();
-    let response = test_state.generate_response(request).await;
+    let response = get(&test_state, "/manga", None).await;
     assert_eq!(response.status(), StatusCode::OK);
 
     let response_body = response.into_body().collect().await.unwrap().to_bytes();
-    let mangas: Vec<Manga> = serde_json::from_slice(&response_body).unwrap();
-    assert_eq!(mangas.len(), 1);
+    let mangas = serde_json::from_slice::<Page<Manga>>(&response_body)
+        .unwrap()
+        .data;
+    assert_eq!(mangas.len(), 2);
 
-    // -----------------------------------------------------------------------------
-    let request = Request::builder()
-        .uri("/manga?offset=10")
-        .body(Body::empty())
-        .unwrap();
-    let response = test_state.generate_response(request).await;
+    let response = get(&test_state, &format!("/manga/{}", manga_id), None).await;
     assert_eq!(response.status(), StatusCode::OK);
 
-    let response_body = response.into_body().collect().await.unwrap().to_bytes();
-    let mangas: Vec<Manga> = serde_json::from_slice(&response_body).unwrap();
-    assert_eq!(mangas.len(), 0);
+    test_state.cleanup().await;
+}
 
-    // -----------------------------------------------------------------------------
+#[tokio::test]
+async fn should_hide_manga_above_user_content_rating() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let mut manga_ids = Vec::new();
+    for content_rating in [None, Some("SUGGESTIVE"), Some("ADULT")] {
+        let manga_id = insert_fake_manga(pool, Some(1)).await;
+        insert_fake_history(pool, user.id, manga_id).await;
+        sqlx::query!(
+            "UPDATE mangas SET title = 'Berserk', source = 'A', content_rating = $1 WHERE id = $2",
+            content_rating,
+            manga_id
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        manga_ids.push(manga_id);
+    }
+
+    let response = get(
+        &test_state,
+        &format!("/manga/{}", manga_ids[1]),
+        Some(&token),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let manga = serde_json::from_slice::<Manga>(&response_body).unwrap();
+    assert_eq!(manga.content_rating.as_deref(), Some("SUGGESTIVE"));
+    assert_eq!(manga.nsfw, Some(0));
+
+    for (max_content_rating, visible) in [("SUGGESTIVE", 2), ("SAFE", 1)] {
+        let request = Request::builder()
+            .method(http::Method::PATCH)
+            .uri("/me")
+            .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(format!(
+                r#"{{"max_content_rating":"{}"}}"#,
+                max_content_rating
+            )))
+            .unwrap();
+        let response = test_state.generate_response(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let uri = format!("/manga/{}", manga_ids[visible]);
+        let response = get(&test_state, &uri, Some(&token)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        for uri in ["/manga", "/manga/search?q=berserk"] {
+            let response = get(&test_state, uri, Some(&token)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let response_body = response.into_body().collect().await.unwrap().to_bytes();
+            let mangas = serde_json::from_slice::<Page<Manga>>(&response_body)
+                .unwrap()
+                .data;
+            assert_eq!(mangas.len(), visible);
+        }
+
+        let response = get(&test_state, "/sources", Some(&token)).await;
+        let response_body = response.into_body().collect().await.unwrap().to_bytes();
+        let sources: serde_json::Value = serde_json::from_slice(&response_body).unwrap();
+        assert_eq!(sources["data"][0]["count"], visible);
+    }
+
+    // `null` goes back to the server default, which lists everything.
     let request = Request::builder()
-        .uri("/manga?offset=1&limit=2")
-        .body(Body::empty())
+        .method(http::Method::PATCH)
+        .uri("/me")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"max_content_rating":null}"#))
         .unwrap();
     let response = test_state.generate_response(request).await;
     assert_eq!(response.status(), StatusCode::OK);
+    let response = get(
+        &test_state,
+        &format!("/manga/{}", manga_ids[2]),
+        Some(&token),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
 
-    let response_body = response.into_body().collect().await.unwrap().to_bytes();
-    let mangas: Vec<Manga> = serde_json::from_slice(&response_body).unwrap();
-    assert_eq!(mangas.len(), 1);
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn index_should_follow_cursor_to_the_last_page() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let mut manga_ids = Vec::new();
+    for tags in [Some(0), None, None] {
+        let manga_id = insert_fake_manga(pool, tags).await;
+        insert_fake_history(pool, user.id, manga_id).await;
+        manga_ids.push(manga_id);
+    }
+    manga_ids.sort_unstable();
+
+    let mut seen = Vec::new();
+    let mut uri = "/manga?limit=2".to_string();
+    loop {
+        let response = get(&test_state, &uri, Some(&token)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body = response.into_body().collect().await.unwrap().to_bytes();
+        let page: Page<Manga> = serde_json::from_slice(&response_body).unwrap();
+        assert!(page.data.len() <= 2);
+        seen.extend(page.data.iter().map(|manga| manga.manga_id));
+
+        match page.next_cursor {
+            Some(cursor) => uri = format!("/manga?limit=2&cursor={}", cursor),
+            None => break,
+        }
+    }
+    assert_eq!(seen, manga_ids);
 
     test_state.cleanup().await;
 }
@@ -97,28 +264,18 @@ async fn index_should_be_ok_when_accessed_with_query() {
 async fn index_should_be_error_when_query_invalid() {
     let mut test_state = AppStateTest::new(true).await;
 
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
     // -----------------------------------------------------------------------------
-    let request = Request::builder()
-        .uri("/manga?offset=-1")
-        .body(Body::empty())
-        .unwrap();
-    let response = test_state.generate_response(request).await;
+    let response = get(&test_state, "/manga?limit=0", Some(&token)).await;
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 
     // -----------------------------------------------------------------------------
-    let request = Request::builder()
-        .uri("/manga?limit=-1")
-        .body(Body::empty())
-        .unwrap();
-    let response = test_state.generate_response(request).await;
+    let response = get(&test_state, "/manga?limit=101", Some(&token)).await;
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 
     // -----------------------------------------------------------------------------
-    let request = Request::builder()
-        .uri("/manga?offset=-1&limit=-1")
-        .body(Body::empty())
-        .unwrap();
-    let response = test_state.generate_response(request).await;
+    let response = get(&test_state, "/manga?cursor=invalid", Some(&token)).await;
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 
     test_state.cleanup().await;
@@ -128,13 +285,11 @@ async fn index_should_be_error_when_query_invalid() {
 async fn show_should_be_ok_when_manga_is_exist() {
     let mut test_state = AppStateTest::new(true).await;
 
+    let (user, token) = test_state.generate_jwt_with_user().await;
     let manga_id = insert_fake_manga(&test_state.app_state.pool, Some(3)).await;
+    insert_fake_history(&test_state.app_state.pool, user.id, manga_id).await;
 
-    let request = Request::builder()
-        .uri(format!("/manga/{}", manga_id))
-        .body(Body::empty())
-        .unwrap();
-    let response = test_state.generate_response(request).await;
+    let response = get(&test_state, &format!("/manga/{}", manga_id), Some(&token)).await;
     assert_eq!(response.status(), StatusCode::OK);
 
     let response_body = response.into_body().collect().await.unwrap().to_bytes();
@@ -150,12 +305,289 @@ async fn show_should_be_ok_when_manga_is_exist() {
 async fn show_should_be_error_when_manga_is_missing() {
     let mut test_state = AppStateTest::new(true).await;
 
-    let request = Request::builder()
-        .uri("/manga/-99999999999")
-        .body(Body::empty())
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let response = get(&test_state, "/manga/-99999999999", Some(&token)).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn search_should_rank_and_filter_results() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let mut manga_ids = Vec::new();
+    for (title, alt_title, author, content_rating) in [
+        ("One Piece", None, Some("Eiichiro Oda"), None),
+        ("Piece of Cake", Some("One Bite"), None, Some("ADULT")),
+        ("Berserk", None, Some("Kentaro Miura"), Some("SUGGESTIVE")),
+    ] {
+        let manga_id = insert_fake_manga(pool, None).await;
+        insert_fake_history(pool, user.id, manga_id).await;
+        sqlx::query!(
+            "UPDATE mangas SET title = $1, alt_title = $2, author = $3, content_rating = $4 WHERE id = $5",
+            title,
+            alt_title,
+            author,
+            content_rating,
+            manga_id
+        )
+        .execute(pool)
+        .await
         .unwrap();
-    let response = test_state.generate_response(request).await;
+        manga_ids.push(manga_id);
+    }
+
+    let search = |uri: &'static str| {
+        let test_state = &test_state;
+        let token = &token;
+        async move {
+            let response = get(test_state, uri, Some(token)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let response_body = response.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice::<Page<Manga>>(&response_body)
+                .unwrap()
+                .data
+                .into_iter()
+                .map(|manga| manga.manga_id)
+                .collect::<Vec<i64>>()
+        }
+    };
+
+    assert_eq!(
+        search("/manga/search?q=one%20piece").await,
+        vec![manga_ids[0], manga_ids[1]]
+    );
+    assert_eq!(search("/manga/search?q=miura").await, vec![manga_ids[2]]);
+    assert_eq!(search("/manga/search?q=berserc").await, vec![manga_ids[2]]);
+    assert_eq!(
+        search("/manga/search?q=piece&nsfw=1").await,
+        vec![manga_ids[1]]
+    );
+    assert_eq!(
+        search("/manga/search?q=piece&content_rating=SAFE").await,
+        vec![manga_ids[0]]
+    );
+    assert!(
+        search("/manga/search?q=piece&tags=unknown")
+            .await
+            .is_empty()
+    );
+
+    sqlx::query!(
+        r#"INSERT INTO tags (id, title, "key", source) VALUES (-1, 'Pirates', 'pirates', 'SOURCE')"#
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "INSERT INTO manga_tags (manga_id, tag_id) VALUES ($1, -1)",
+        manga_ids[0]
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        search("/manga/search?q=piece&tags=pirates,%20pirates,").await,
+        vec![manga_ids[0]]
+    );
+
+    let response = get(&test_state, "/manga/search?q=piece&limit=1", Some(&token)).await;
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let page: Page<Manga> = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(page.data.len(), 1);
+    let response = get(
+        &test_state,
+        &format!(
+            "/manga/search?q=piece&limit=1&cursor={}",
+            page.next_cursor.unwrap()
+        ),
+        Some(&token),
+    )
+    .await;
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let next_page: Page<Manga> = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(next_page.data.len(), 1);
+    assert_ne!(next_page.data[0].manga_id, page.data[0].manga_id);
+
+    let response = get(&test_state, "/manga/search?q=", Some(&token)).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = get(
+        &test_state,
+        &format!("/manga/search?q=piece&cursor={}", encode_cursor(&-1i64)),
+        Some(&token),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    test_state.cleanup().await;
+}
+
+async fn sync_title(
+    test_state: &AppStateTest,
+    user_id: i64,
+    manga_id: i64,
+    title: &str,
+    policy: MetadataPolicy,
+) {
+    let pool = &test_state.app_state.pool;
+    let mut manga = get_manga_by_id(pool, MangaScope::global(), manga_id)
+        .await
+        .unwrap();
+    manga.title = title.to_string();
+
+    let mut tx = pool.begin().await.unwrap();
+    insert_mangas(&mut tx, &[Arc::new(manga)], Some(user_id), policy)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+}
+
+async fn current_title(test_state: &AppStateTest, manga_id: i64) -> String {
+    get_manga_by_id(&test_state.app_state.pool, MangaScope::global(), manga_id)
+        .await
+        .unwrap()
+        .title
+}
+
+#[tokio::test]
+async fn revisions_should_list_changes_newest_first() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (admin, admin_token) = test_state.generate_jwt_with_user().await;
+    let (user, token) = test_state.generate_jwt_with_email("other@email.com").await;
+    let manga_id = insert_fake_manga(pool, None).await;
+    insert_fake_history(pool, admin.id, manga_id).await;
+    insert_fake_history(pool, user.id, manga_id).await;
+
+    sync_title(
+        &test_state,
+        admin.id,
+        manga_id,
+        "First",
+        MetadataPolicy::Overwrite,
+    )
+    .await;
+    sync_title(
+        &test_state,
+        user.id,
+        manga_id,
+        "Second",
+        MetadataPolicy::Overwrite,
+    )
+    .await;
+    // Syncing the same values again does not add a revision.
+    sync_title(
+        &test_state,
+        user.id,
+        manga_id,
+        "Second",
+        MetadataPolicy::Overwrite,
+    )
+    .await;
+
+    let uri = format!("/manga/{}/revisions", manga_id);
+
+    let response = get(&test_state, &uri, Some(&token)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let page: Page<MangaRevision> = serde_json::from_slice(&response_body).unwrap();
+    // The first insert is recorded as well, with every field changed from null.
+    assert_eq!(page.data.len(), 3);
+    assert_eq!(page.data[0].changes["title"]["from"], "First");
+    assert_eq!(page.data[0].changes["title"]["to"], "Second");
+    assert_eq!(page.data[0].user_id, Some(user.id));
+    // Other users' ids are hidden from non-admins.
+    assert_eq!(page.data[1].user_id, None);
+    assert_eq!(
+        page.data[2].changes["title"]["from"],
+        serde_json::Value::Null
+    );
+
+    let response = get(&test_state, &format!("{}?limit=1", uri), Some(&admin_token)).await;
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let page: Page<MangaRevision> = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(page.data.len(), 1);
+    assert_eq!(page.data[0].user_id, Some(user.id));
+
+    let response = get(
+        &test_state,
+        &format!("{}?limit=1&cursor={}", uri, page.next_cursor.unwrap()),
+        Some(&admin_token),
+    )
+    .await;
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let page: Page<MangaRevision> = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(page.data.len(), 1);
+    assert_eq!(page.data[0].user_id, Some(admin.id));
+    assert_eq!(page.data[0].changes["title"]["to"], "First");
+    assert!(page.next_cursor.is_some());
+
+    // Manga outside the caller's library stay hidden.
+    let other_manga_id = insert_fake_manga(pool, None).await;
+    let response = get(
+        &test_state,
+        &format!("/manga/{}/revisions", other_manga_id),
+        Some(&token),
+    )
+    .await;
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 
     test_state.cleanup().await;
 }
+
+#[tokio::test]
+async fn most_common_policy_should_keep_majority_value() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (first, _) = test_state.generate_jwt_with_email("first@email.com").await;
+    let (second, _) = test_state.generate_jwt_with_email("second@email.com").await;
+    let (third, _) = test_state.generate_jwt_with_email("third@email.com").await;
+    let manga_id = insert_fake_manga(pool, None).await;
+
+    let policy = MetadataPolicy::MostCommon;
+    sync_title(&test_state, first.id, manga_id, "Berserk", policy).await;
+    sync_title(&test_state, second.id, manga_id, "Berserk", policy).await;
+    sync_title(&test_state, third.id, manga_id, "Beserk (outdated)", policy).await;
+
+    assert_eq!(current_title(&test_state, manga_id).await, "Berserk");
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn most_recent_policy_should_ignore_stale_resyncs() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (first, _) = test_state.generate_jwt_with_email("first@email.com").await;
+    let (second, _) = test_state.generate_jwt_with_email("second@email.com").await;
+    let manga_id = insert_fake_manga(pool, None).await;
+
+    let policy = MetadataPolicy::MostRecent;
+    sync_title(&test_state, first.id, manga_id, "Old title", policy).await;
+    sync_title(&test_state, second.id, manga_id, "New title", policy).await;
+    // The first client keeps sending its unchanged, outdated value.
+    sync_title(&test_state, first.id, manga_id, "Old title", policy).await;
+    assert_eq!(current_title(&test_state, manga_id).await, "New title");
+
+    // With blind overwrite the stale resync wins.
+    sync_title(
+        &test_state,
+        first.id,
+        manga_id,
+        "Old title",
+        MetadataPolicy::Overwrite,
+    )
+    .await;
+    assert_eq!(current_title(&test_state, manga_id).await, "Old title");
+
+    test_state.cleanup().await;
+}
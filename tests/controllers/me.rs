@@ -1,8 +1,12 @@
-use axum::{body::Body, http::Request, http::StatusCode};
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
 use http_body_util::BodyExt;
 use rustatsu_sync::model::User;
+use serde_json::json;
 
-use crate::AppStateTest;
+use crate::{AppStateTest, insert_fake_favourite, insert_fake_history, insert_fake_manga};
 
 #[tokio::test]
 async fn should_be_error_when_accessed_without_auth() {
@@ -40,3 +44,202 @@ async fn should_be_ok_when_accessed_with_auth() {
 
     test_state.cleanup().await;
 }
+
+#[tokio::test]
+async fn destroy_should_be_error_when_password_is_incorrect() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let request = Request::builder()
+        .method("DELETE")
+        .uri("/me")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "password": "incorrect-password" })).unwrap(),
+        ))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let result = sqlx::query!(r#"SELECT count(*) FROM users"#)
+        .fetch_one(&test_state.app_state.pool)
+        .await
+        .unwrap();
+    assert_eq!(result.count, Some(1));
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn destroy_should_purge_user_data() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let (other_user, _) = test_state.generate_jwt_with_email("other@localhost").await;
+
+    let owned_manga_id = insert_fake_manga(pool, Some(2)).await;
+    let shared_manga_id = insert_fake_manga(pool, Some(2)).await;
+    insert_fake_favourite(pool, user.id, owned_manga_id).await;
+    insert_fake_history(pool, user.id, owned_manga_id).await;
+    insert_fake_history(pool, user.id, shared_manga_id).await;
+    insert_fake_history(pool, other_user.id, shared_manga_id).await;
+
+    let request = Request::builder()
+        .method("DELETE")
+        .uri("/me")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "password": "password" })).unwrap(),
+        ))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let result = sqlx::query!(r#"SELECT count(*) FROM users"#)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(result.count, Some(1));
+
+    let result = sqlx::query!(r#"SELECT id FROM mangas"#)
+        .fetch_all(pool)
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, shared_manga_id);
+
+    let result = sqlx::query!(r#"SELECT count(*) FROM tags"#)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert_eq!(result.count, Some(2));
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn update_should_change_nickname() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+
+    let request = Request::builder()
+        .method("PATCH")
+        .uri("/me")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "nickname": "reader" })).unwrap(),
+        ))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let user_result: User = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(user_result.email, user.email);
+    assert_eq!(user_result.nickname, Some("reader".to_string()));
+
+    // A missing field keeps the nickname, `null` clears it.
+    for (body, nickname) in [
+        (json!({}), Some("reader".to_string())),
+        (json!({ "nickname": null }), None),
+    ] {
+        let request = Request::builder()
+            .method("PATCH")
+            .uri("/me")
+            .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap();
+        let response = test_state.generate_response(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body = response.into_body().collect().await.unwrap().to_bytes();
+        let user_result: User = serde_json::from_slice(&response_body).unwrap();
+        assert_eq!(user_result.nickname, nickname);
+    }
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn update_should_be_error_when_email_change_is_not_verified() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+    test_state.generate_jwt_with_email("taken@localhost").await;
+
+    // -----------------------------------------------------------------------------
+    let request = Request::builder()
+        .method("PATCH")
+        .uri("/me")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "email": "new@localhost" })).unwrap(),
+        ))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // -----------------------------------------------------------------------------
+    let request = Request::builder()
+        .method("PATCH")
+        .uri("/me")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "email": "new@localhost", "password": "incorrect" }))
+                .unwrap(),
+        ))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // -----------------------------------------------------------------------------
+    let request = Request::builder()
+        .method("PATCH")
+        .uri("/me")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "email": "taken@localhost", "password": "password" }))
+                .unwrap(),
+        ))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn update_should_change_email() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let request = Request::builder()
+        .method("PATCH")
+        .uri("/me")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "email": "new@localhost", "password": "password" }))
+                .unwrap(),
+        ))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let user_result: User = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(user_result.email, "new@localhost");
+
+    test_state.cleanup().await;
+}
@@ -1,4 +1,19 @@
+pub mod admin;
 pub mod auth;
+pub mod bookmarks;
+pub mod browse;
+pub mod chapters;
+pub mod continue_reading;
+pub mod covers;
+pub mod export;
 pub mod home;
+pub mod links;
+pub mod lists;
 pub mod manga;
 pub mod me;
+pub mod recommendations;
+pub mod settings;
+pub mod sources;
+pub mod stats;
+pub mod timeline;
+pub mod tracking;
@@ -0,0 +1,120 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::model::Recommendation;
+use serde_json::json;
+
+use crate::{AppStateTest, insert_fake_favourite, insert_fake_history, insert_fake_manga};
+
+async fn recommendations(test_state: &AppStateTest, token: &str, uri: &str) -> Vec<Recommendation> {
+    let request = Request::builder()
+        .uri(uri)
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&response_body).unwrap()
+}
+
+#[tokio::test]
+async fn should_recommend_from_tags_and_similar_users() {
+    let mut test_state = AppStateTest::new(true).await;
+    let pool = &test_state.app_state.pool;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let (other, other_token) = test_state.generate_jwt_with_email("other@email.com").await;
+
+    let read = insert_fake_manga(pool, Some(1)).await;
+    let favourited_by_other = insert_fake_manga(pool, Some(1)).await;
+    let same_tag = insert_fake_manga(pool, Some(1)).await;
+    let nsfw = insert_fake_manga(pool, Some(1)).await;
+    let unrelated = insert_fake_manga(pool, Some(1)).await;
+
+    insert_fake_favourite(pool, user.id, read).await;
+    insert_fake_favourite(pool, other.id, read).await;
+    insert_fake_favourite(pool, other.id, favourited_by_other).await;
+    insert_fake_favourite(pool, other.id, nsfw).await;
+    insert_fake_history(pool, other.id, same_tag).await;
+    insert_fake_history(pool, other.id, unrelated).await;
+
+    // Tags of different sources match by title.
+    sqlx::query!(
+        "UPDATE tags SET title = 'Seinen' WHERE id IN (SELECT tag_id FROM manga_tags WHERE manga_id = ANY($1))",
+        &[read, same_tag]
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE mangas SET content_rating = 'ADULT' WHERE id = $1",
+        nsfw
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let result = recommendations(&test_state, &token, "/me/recommendations").await;
+    let manga_ids: Vec<i64> = result.iter().map(|r| r.manga.manga_id).collect();
+    assert_eq!(manga_ids.len(), 2);
+    assert!(manga_ids.contains(&favourited_by_other));
+    assert!(manga_ids.contains(&same_tag));
+    let by_tag = result
+        .iter()
+        .find(|r| r.manga.manga_id == same_tag)
+        .unwrap();
+    assert_eq!(by_tag.tag_score, 1.0);
+    assert_eq!(by_tag.collaborative_score, 0.0);
+    let by_user = result
+        .iter()
+        .find(|r| r.manga.manga_id == favourited_by_other)
+        .unwrap();
+    assert_eq!(by_user.collaborative_score, 1.0);
+
+    let result =
+        recommendations(&test_state, &token, "/me/recommendations?include_nsfw=true").await;
+    assert_eq!(result.len(), 3);
+    assert!(result.iter().any(|r| r.manga.manga_id == nsfw));
+
+    // Users who opt out neither contribute signals nor expose their library.
+    let request = Request::builder()
+        .method(http::Method::PATCH)
+        .uri("/me")
+        .header(
+            http::header::AUTHORIZATION,
+            format!("bearer {}", other_token),
+        )
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "share_reading_signals": false })).unwrap(),
+        ))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let result = recommendations(&test_state, &token, "/me/recommendations").await;
+    assert!(result.is_empty());
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn should_be_error_when_query_invalid() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let request = Request::builder()
+        .uri("/me/recommendations?limit=0")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    test_state.cleanup().await;
+}
@@ -0,0 +1,96 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::model::UserSetting;
+use serde_json::{Value, json};
+
+use crate::AppStateTest;
+
+async fn store(test_state: &AppStateTest, token: &str, body: Value) -> StatusCode {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/resource/settings")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+
+    test_state.generate_response(request).await.status()
+}
+
+#[tokio::test]
+async fn store_should_merge_by_updated_at() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let status = store(
+        &test_state,
+        &token,
+        json!({
+            "settings": [
+                { "namespace": "reader", "key": "mode", "value": "webtoon", "updated_at": 10, "deleted_at": 0 },
+                { "namespace": "reader", "key": "zoom", "value": 1.5, "updated_at": 10, "deleted_at": 0 },
+                { "namespace": "appearance", "key": "theme", "value": { "dark": true }, "updated_at": 10, "deleted_at": 0 }
+            ],
+            "timestamp": 10
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let status = store(
+        &test_state,
+        &token,
+        json!({
+            "settings": [
+                { "namespace": "reader", "key": "mode", "value": "standard", "updated_at": 5, "deleted_at": 0 },
+                { "namespace": "reader", "key": "zoom", "value": 2.0, "updated_at": 20, "deleted_at": 0 }
+            ],
+            "timestamp": 20
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let request = Request::builder()
+        .uri("/resource/settings?namespace=reader")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let mut user_setting: UserSetting = serde_json::from_slice(&response_body).unwrap();
+    user_setting.settings.sort_by(|a, b| a.key.cmp(&b.key));
+    assert_eq!(user_setting.settings.len(), 2);
+    assert_eq!(user_setting.settings[0].value, json!("webtoon"));
+    assert_eq!(user_setting.settings[1].value, json!(2.0));
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn store_should_be_error_when_key_is_empty() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let status = store(
+        &test_state,
+        &token,
+        json!({
+            "settings": [
+                { "namespace": "reader", "key": "", "value": null, "updated_at": 10, "deleted_at": 0 }
+            ],
+            "timestamp": 10
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    test_state.cleanup().await;
+}
@@ -0,0 +1,117 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::model::UserSource;
+use serde_json::json;
+
+use crate::AppStateTest;
+
+#[tokio::test]
+async fn store_should_sync_sources_with_tombstones() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let body = json!({
+        "sources": [
+            { "source": "MANGADEX", "enabled": 1, "pinned": 1, "sort_key": 0, "added_in": 1, "used_at": 10, "deleted_at": 0 },
+            { "source": "COMICK_FUN", "enabled": 0, "pinned": 0, "sort_key": 1, "added_in": 1, "used_at": 0, "deleted_at": 0 }
+        ],
+        "timestamp": 1
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/resource/sources")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json!({
+        "sources": [
+            { "source": "COMICK_FUN", "enabled": 0, "pinned": 0, "sort_key": 1, "added_in": 1, "used_at": 0, "deleted_at": 20 }
+        ],
+        "timestamp": 2
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/resource/sources")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let mut user_source: UserSource = serde_json::from_slice(&response_body).unwrap();
+    user_source.sources.sort_by_key(|source| source.sort_key);
+    assert_eq!(user_source.sources.len(), 2);
+    assert_eq!(user_source.sources[0].pinned, 1);
+    assert_eq!(user_source.sources[1].deleted_at, 20);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn store_should_keep_latest_of_duplicate_sources() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let body = json!({
+        "sources": [
+            { "source": "MANGADEX", "enabled": 1, "pinned": 0, "sort_key": 0, "added_in": 1, "used_at": 20, "deleted_at": 0 },
+            { "source": "MANGADEX", "enabled": 0, "pinned": 1, "sort_key": 1, "added_in": 1, "used_at": 10, "deleted_at": 0 }
+        ],
+        "timestamp": 1
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/resource/sources")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let user_source: UserSource = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(user_source.sources.len(), 1);
+    assert_eq!(user_source.sources[0].used_at, 20);
+    assert_eq!(user_source.sources[0].enabled, 1);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn store_should_be_error_when_source_is_too_long() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    for source in [String::new(), "A".repeat(121)] {
+        let body = json!({
+            "sources": [
+                { "source": source, "enabled": 1, "pinned": 0, "sort_key": 0, "added_in": 1, "used_at": 0, "deleted_at": 0 }
+            ],
+            "timestamp": 1
+        });
+        let request = Request::builder()
+            .method("POST")
+            .uri("/resource/sources")
+            .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap();
+        let response = test_state.generate_response(request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    test_state.cleanup().await;
+}
@@ -0,0 +1,119 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::model::{ReadingSummary, SourceStat, TagStat, WeeklyChapters};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::{AppStateTest, insert_fake_history, insert_fake_manga};
+
+async fn get<T: DeserializeOwned>(test_state: &AppStateTest, token: &str, uri: &str) -> T {
+    let request = Request::builder()
+        .uri(uri)
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&response_body).unwrap()
+}
+
+#[tokio::test]
+async fn should_be_error_when_accessed_without_auth() {
+    let test_state = AppStateTest::new(false).await;
+
+    let request = Request::builder()
+        .uri("/me/stats")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn should_compute_stats_from_history() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (user, token) = test_state.generate_jwt_with_user().await;
+    let mut manga_ids = Vec::new();
+    for _ in 0..2 {
+        let manga_id = insert_fake_manga(&test_state.app_state.pool, Some(3)).await;
+        insert_fake_history(&test_state.app_state.pool, user.id, manga_id).await;
+        manga_ids.push(manga_id);
+    }
+    // Without chapter reads synced, chapters come from the timeline and the history position.
+    sqlx::query!(
+        r#"
+        INSERT INTO history_timeline (manga_id, chapter_id, page, percent, recorded_at, user_id)
+        VALUES ($1, 2, 0, 0.2, 1, $2), ($1, 1, 0, 0.1, 1, $2)
+    "#,
+        manga_ids[0],
+        user.id
+    )
+    .execute(&test_state.app_state.pool)
+    .await
+    .unwrap();
+
+    let summary: ReadingSummary = get(&test_state, &token, "/me/stats").await;
+    assert_eq!(summary.started, 2);
+    assert_eq!(summary.finished, 0);
+    assert_eq!(summary.completion_rate, 0.0);
+    assert_eq!(summary.chapters_read, 3);
+
+    let weekly: Vec<WeeklyChapters> = get(&test_state, &token, "/me/stats/weekly").await;
+    assert_eq!(weekly.len(), 1);
+    assert_eq!(weekly[0].chapters, 3);
+
+    let summary: ReadingSummary = get(&test_state, &token, "/me/stats?from=2").await;
+    assert_eq!(summary.started, 0);
+
+    let tags: Vec<TagStat> = get(&test_state, &token, "/me/stats/tags?limit=4").await;
+    assert_eq!(tags.len(), 4);
+
+    let sources: Vec<SourceStat> = get(&test_state, &token, "/me/stats/sources").await;
+    assert_eq!(sources.iter().map(|s| s.count).sum::<i64>(), 2);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn should_refresh_cached_stats_after_sync() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let weekly: Vec<WeeklyChapters> = get(&test_state, &token, "/me/stats/weekly").await;
+    assert!(weekly.is_empty());
+
+    let body = json!({
+        "chapters": [
+            // Monday 2024-01-01 and Sunday 2024-01-07, UTC.
+            { "manga_id": 1, "chapter_id": 1, "read_at": 1_704_067_200_000_i64, "percent": 1.0, "deleted_at": 0 },
+            { "manga_id": 1, "chapter_id": 2, "read_at": 1_704_585_600_000_i64, "percent": 1.0, "deleted_at": 0 },
+            { "manga_id": 1, "chapter_id": 3, "read_at": 1_704_672_000_000_i64, "percent": 1.0, "deleted_at": 0 }
+        ],
+        "timestamp": 1
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/resource/chapters")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let weekly: Vec<WeeklyChapters> = get(&test_state, &token, "/me/stats/weekly").await;
+    assert_eq!(weekly.len(), 2);
+    assert_eq!(weekly[0].week, 1_704_067_200_000);
+    assert_eq!(weekly[0].chapters, 2);
+    assert_eq!(weekly[1].chapters, 1);
+
+    test_state.cleanup().await;
+}
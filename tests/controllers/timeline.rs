@@ -0,0 +1,111 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::model::TimelineEntry;
+use serde_json::{Value, json};
+
+use crate::AppStateTest;
+
+fn history(chapter_id: i64, page: i16, percent: f32, updated_at: i64) -> Value {
+    json!({
+        "manga_id": 10,
+        "manga": {
+            "manga_id": 10,
+            "title": "Title",
+            "alt_title": null,
+            "url": "/manga/10",
+            "public_url": "https://localhost/manga/10",
+            "rating": 0.5,
+            "nsfw": 0,
+            "content_rating": null,
+            "cover_url": "https://localhost/cover.jpg",
+            "large_cover_url": null,
+            "state": null,
+            "author": null,
+            "source": "SOURCE",
+            "tags": []
+        },
+        "created_at": 1,
+        "updated_at": updated_at,
+        "chapter_id": chapter_id,
+        "page": page,
+        "scroll": 0.0,
+        "percent": percent,
+        "chapters": 10,
+        "deleted_at": 0
+    })
+}
+
+async fn store_history(test_state: &AppStateTest, token: &str, history: Value) {
+    let body = json!({ "history": [history], "timestamp": 1 });
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/resource/history")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn timeline(test_state: &AppStateTest, token: &str, uri: &str) -> Vec<TimelineEntry> {
+    let request = Request::builder()
+        .uri(uri)
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&response_body).unwrap()
+}
+
+#[tokio::test]
+async fn should_record_progress_moving_forward() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    store_history(&test_state, &token, history(1, 3, 0.1, 100)).await;
+    // Same position uploaded again by another device.
+    store_history(&test_state, &token, history(1, 3, 0.1, 150)).await;
+    store_history(&test_state, &token, history(1, 8, 0.1, 200)).await;
+    // Stale upload from a device that was offline.
+    store_history(&test_state, &token, history(2, 0, 0.2, 50)).await;
+    store_history(&test_state, &token, history(2, 1, 0.2, 300)).await;
+
+    let entries = timeline(&test_state, &token, "/me/timeline").await;
+    let recorded: Vec<i64> = entries.iter().map(|e| e.recorded_at).collect();
+    assert_eq!(recorded, vec![300, 200, 100]);
+    assert_eq!(entries[0].chapter_id, 2);
+    assert_eq!(entries[0].manga.title, "Title");
+
+    let entries = timeline(&test_state, &token, "/me/timeline?from=150&to=300").await;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].page, 8);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn should_not_record_moving_back_to_earlier_chapter() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    store_history(&test_state, &token, history(2, 5, 0.2, 100)).await;
+    // Re-reading an earlier chapter, further into it than the current page.
+    store_history(&test_state, &token, history(1, 9, 0.1, 200)).await;
+    store_history(&test_state, &token, history(1, 10, 0.11, 300)).await;
+
+    let entries = timeline(&test_state, &token, "/me/timeline").await;
+    let recorded: Vec<i64> = entries.iter().map(|e| e.recorded_at).collect();
+    assert_eq!(recorded, vec![300, 100]);
+
+    test_state.cleanup().await;
+}
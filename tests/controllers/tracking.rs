@@ -0,0 +1,95 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rustatsu_sync::model::UserTrack;
+use serde_json::{Value, json};
+
+use crate::AppStateTest;
+
+fn track(chapters_new: i32, updated_at: i64) -> Value {
+    json!({
+        "manga_id": 10,
+        "manga": {
+            "manga_id": 10,
+            "title": "Title",
+            "alt_title": null,
+            "url": "/manga/10",
+            "public_url": "https://localhost/manga/10",
+            "rating": 0.5,
+            "nsfw": 0,
+            "content_rating": null,
+            "cover_url": "https://localhost/cover.jpg",
+            "large_cover_url": null,
+            "state": null,
+            "author": null,
+            "source": "SOURCE",
+            "tags": []
+        },
+        "last_chapter_id": 3,
+        "chapters_total": 12,
+        "chapters_new": chapters_new,
+        "last_check_at": updated_at,
+        "last_chapter_date": 1,
+        "updated_at": updated_at,
+        "deleted_at": 0
+    })
+}
+
+async fn store(test_state: &AppStateTest, token: &str, tracks: Vec<Value>) -> StatusCode {
+    let body = json!({ "tracks": tracks, "timestamp": 1 });
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/resource/tracking")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+
+    test_state.generate_response(request).await.status()
+}
+
+#[tokio::test]
+async fn should_be_error_when_accessed_without_auth() {
+    let test_state = AppStateTest::new(false).await;
+
+    let request = Request::builder()
+        .uri("/resource/tracking")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn store_should_keep_newest_tracker_state() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let status = store(&test_state, &token, vec![track(2, 10), track(3, 5)]).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let status = store(&test_state, &token, vec![track(0, 8)]).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let request = Request::builder()
+        .uri("/resource/tracking")
+        .header(http::header::AUTHORIZATION, format!("bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = response.into_body().collect().await.unwrap().to_bytes();
+    let user_track: UserTrack = serde_json::from_slice(&response_body).unwrap();
+    assert_eq!(user_track.tracks.len(), 1);
+    assert_eq!(user_track.tracks[0].chapters_new, 2);
+    assert_eq!(user_track.tracks[0].updated_at, 10);
+    assert_eq!(user_track.tracks[0].manga.title, "Title");
+
+    test_state.cleanup().await;
+}
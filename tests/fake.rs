@@ -3,6 +3,7 @@ use std::sync::Arc;
 use fake::{Fake, faker::name::en::Name};
 use rand::Rng;
 use rustatsu_sync::{
+    config::MetadataPolicy,
     db::{manga::insert_mangas, manga_tags::insert_manga_tags, tags::insert_tags},
     model::{Manga, MangaTagEntity, Tag},
 };
@@ -21,7 +22,9 @@ pub async fn insert_fake_manga(pool: &PgPool, tags_size: Option<u64>) -> i64 {
     let (manga, manga_tags) = create_fake_manga(tags);
     let manga_id = manga.manga_id;
 
-    insert_mangas(&mut tx, &[Arc::new(manga)]).await.unwrap();
+    insert_mangas(&mut tx, &[Arc::new(manga)], None, MetadataPolicy::Overwrite)
+        .await
+        .unwrap();
     insert_manga_tags(&mut tx, &manga_tags).await.unwrap();
 
     tx.commit().await.unwrap();
@@ -73,3 +76,49 @@ fn create_fake_tag() -> Tag {
         source: "source".to_string(),
     }
 }
+
+pub async fn insert_fake_history(pool: &PgPool, user_id: i64, manga_id: i64) {
+    sqlx::query(
+        r#"
+        INSERT INTO history
+            (manga_id, created_at, updated_at, chapter_id, page, scroll, percent, chapters, deleted_at, user_id)
+        VALUES
+            ($1, 1, 1, 1, 0, 0, 0.5, 10, 0, $2);
+    "#,
+    )
+    .bind(manga_id)
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+pub async fn insert_fake_favourite(pool: &PgPool, user_id: i64, manga_id: i64) {
+    sqlx::query(
+        r#"
+        INSERT INTO categories
+            (id, created_at, sort_key, title, "order", user_id, track, show_in_lib, deleted_at)
+        VALUES
+            (1, 1, 0, 'Reading', 'NAME', $1, true, true, 0)
+        ON CONFLICT (id, user_id) DO NOTHING;
+    "#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        INSERT INTO favourites
+            (manga_id, category_id, user_id, sort_key, created_at, deleted_at)
+        VALUES
+            ($1, 1, $2, 0, 1, 0);
+    "#,
+    )
+    .bind(manga_id)
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .unwrap();
+}
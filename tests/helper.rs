@@ -63,13 +63,13 @@ impl AppStateTest {
     }
 
     pub async fn generate_jwt_with_user(&self) -> (User, String) {
-        let (user, _) = create_user(
-            &self.app_state.pool,
-            "test@email.com".to_string(),
-            "password".into(),
-        )
-        .await
-        .expect("Failed create user");
+        self.generate_jwt_with_email("test@email.com").await
+    }
+
+    pub async fn generate_jwt_with_email(&self, email: &str) -> (User, String) {
+        let (user, _) = create_user(&self.app_state.pool, email.to_string(), "password".into())
+            .await
+            .expect("Failed create user");
 
         let token = encode_jwt(user.id, &self.app_state.config.jwt).unwrap();
 
@@ -0,0 +1,59 @@
+use axum::{body::Body, http::Request, http::StatusCode};
+
+use crate::AppStateTest;
+
+#[tokio::test]
+async fn should_throw_error_when_request_does_not_contain_header_authorization() {
+    let test_state = AppStateTest::new(false).await;
+
+    let request = Request::builder()
+        .uri("/admin/users")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn should_throw_error_when_user_is_not_admin() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (admin, _) = test_state.generate_jwt_with_user().await;
+    assert!(admin.is_admin);
+
+    let (user, token) = test_state.generate_jwt_with_email("member@email.com").await;
+    assert!(!user.is_admin);
+
+    let request = Request::builder()
+        .uri("/admin/users")
+        .header(
+            axum::http::header::AUTHORIZATION,
+            format!("bearer {}", token),
+        )
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    test_state.cleanup().await;
+}
+
+#[tokio::test]
+async fn should_be_ok_when_user_is_admin() {
+    let mut test_state = AppStateTest::new(true).await;
+
+    let (_, token) = test_state.generate_jwt_with_user().await;
+
+    let request = Request::builder()
+        .uri("/admin/users")
+        .header(
+            axum::http::header::AUTHORIZATION,
+            format!("bearer {}", token),
+        )
+        .body(Body::empty())
+        .unwrap();
+    let response = test_state.generate_response(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    test_state.cleanup().await;
+}